@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Per-directory token caps from `.ombudget` files.
+///
+/// Each non-comment line is a directory prefix and a token cap separated by
+/// whitespace, e.g. `vendor/ 20000`. When prefixes nest, a file counts
+/// against every cap containing it. Lines that don't parse are ignored, as
+/// in `.omignore`.
+#[derive(Debug, Default)]
+pub struct DirBudgets {
+    caps: Vec<(String, usize)>,
+}
+
+impl DirBudgets {
+    pub fn load(root: &Path) -> Self {
+        let mut caps = Vec::new();
+
+        if let Some(home) = dirs::home_dir() {
+            if let Ok(content) = fs::read_to_string(home.join(".ombudget")) {
+                caps.extend(Self::parse(&content));
+            }
+        }
+
+        if let Ok(content) = fs::read_to_string(root.join(".ombudget")) {
+            caps.extend(Self::parse(&content));
+        }
+
+        DirBudgets { caps }
+    }
+
+    fn parse(content: &str) -> Vec<(String, usize)> {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let prefix = parts.next()?.trim_end_matches('/');
+                let cap = parts.next()?.parse().ok()?;
+                if prefix.is_empty() || parts.next().is_some() {
+                    return None;
+                }
+                Some((prefix.to_string(), cap))
+            })
+            .collect()
+    }
+
+    /// Every prefix containing `path` with its cap, most specific first.
+    /// Later entries for the same prefix (the repository's file) win.
+    fn caps_for(&self, path: &str) -> Vec<(&str, usize)> {
+        let mut caps: Vec<(&str, usize)> = Vec::new();
+        for (prefix, cap) in &self.caps {
+            let contains = path
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.starts_with('/'));
+            if !contains {
+                continue;
+            }
+            match caps.iter_mut().find(|(seen, _)| *seen == prefix) {
+                Some(entry) => entry.1 = *cap,
+                None => caps.push((prefix, *cap)),
+            }
+        }
+        caps.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        caps
+    }
+}
+
+/// Running per-prefix token totals for one `cat` selection.
+pub struct BudgetTracker<'a> {
+    budgets: &'a DirBudgets,
+    used: HashMap<&'a str, usize>,
+}
+
+impl<'a> BudgetTracker<'a> {
+    pub fn new(budgets: &'a DirBudgets) -> Self {
+        BudgetTracker {
+            budgets,
+            used: HashMap::new(),
+        }
+    }
+
+    /// Account the tokens of `path` against every cap containing it,
+    /// counted only if a cap applies. Returns the most specific prefix whose
+    /// cap would be exceeded instead, leaving all totals unchanged.
+    pub fn admit(&mut self, path: &str, tokens: impl FnOnce() -> usize) -> Result<(), &'a str> {
+        let caps = self.budgets.caps_for(path);
+        if caps.is_empty() {
+            return Ok(());
+        }
+
+        let tokens = tokens();
+        for &(prefix, cap) in &caps {
+            if self.used.get(prefix).copied().unwrap_or(0) + tokens > cap {
+                return Err(prefix);
+            }
+        }
+        for (prefix, _) in caps {
+            *self.used.entry(prefix).or_insert(0) += tokens;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let caps = DirBudgets::parse(
+            "# caps\n\nvendor/ 20000\ndocs 500\nbad line here\nnocap\nsrc/ lots\n",
+        );
+        assert_eq!(
+            caps,
+            vec![("vendor".to_string(), 20000), ("docs".to_string(), 500)]
+        );
+    }
+
+    #[test]
+    fn test_caps_for_nested_prefixes() {
+        let budgets = DirBudgets {
+            caps: DirBudgets::parse("vendor/ 100\nvendor/big/ 10\nvendor/ 50\n"),
+        };
+
+        assert_eq!(budgets.caps_for("vendor/a.rs"), vec![("vendor", 50)]);
+        assert_eq!(
+            budgets.caps_for("vendor/big/b.rs"),
+            vec![("vendor/big", 10), ("vendor", 50)]
+        );
+        assert!(budgets.caps_for("vendorized/c.rs").is_empty());
+        assert!(budgets.caps_for("src/main.rs").is_empty());
+    }
+
+    #[test]
+    fn test_tracker() {
+        let budgets = DirBudgets {
+            caps: DirBudgets::parse("vendor/ 100\n"),
+        };
+        let mut tracker = BudgetTracker::new(&budgets);
+
+        assert_eq!(tracker.admit("vendor/a.rs", || 60), Ok(()));
+        assert_eq!(tracker.admit("vendor/b.rs", || 60), Err("vendor"));
+        assert_eq!(tracker.admit("vendor/c.rs", || 40), Ok(()));
+        assert_eq!(tracker.admit("src/main.rs", || 1000), Ok(()));
+    }
+
+    #[test]
+    fn test_tracker_nested_prefixes() {
+        let budgets = DirBudgets {
+            caps: DirBudgets::parse("vendor/ 100\nvendor/big/ 80\n"),
+        };
+        let mut tracker = BudgetTracker::new(&budgets);
+
+        // Files under `vendor/big/` also count against `vendor/`.
+        assert_eq!(tracker.admit("vendor/big/a.rs", || 70), Ok(()));
+        assert_eq!(tracker.admit("vendor/b.rs", || 40), Err("vendor"));
+        assert_eq!(tracker.admit("vendor/big/c.rs", || 20), Err("vendor/big"));
+        assert_eq!(tracker.admit("vendor/big/d.rs", || 10), Ok(()));
+        assert_eq!(tracker.admit("vendor/e.rs", || 20), Ok(()));
+        assert_eq!(tracker.admit("vendor/f.rs", || 1), Err("vendor"));
+    }
+}
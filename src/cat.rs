@@ -1,21 +1,39 @@
+use crate::budget::{BudgetTracker, DirBudgets};
 use crate::cli::CatArgs;
+use crate::framed;
+use crate::fsutil::is_empty_file;
 use crate::git;
 use crate::ignore::IgnorePatterns;
-use crate::output::{self, CatOutput, FileOutput, OutputFormat};
-use crate::scorer::{score_files, ScoredFile};
-use crate::session::Session;
+use crate::output::{
+    self, BindingLimit, CatOutput, FileError, FileGroup, FileOutput, ManifestFile, OutputFormat,
+    RunManifest,
+};
+use crate::redact::Redactor;
+use crate::scorer::{self, ScoredFile, Scorer};
+use crate::session::{Session, SessionFormat};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 pub fn run(args: CatArgs) -> Result<(), Box<dyn std::error::Error>> {
     let path = PathBuf::from(args.path.clone().unwrap_or_else(|| ".".to_string()));
-    let root = git::repo_root(&path)?;
+    let root = if args.stdin_content {
+        fs::canonicalize(&path)?
+    } else {
+        git::repo_root(&path)?
+    };
 
     let session_name = args
         .session
         .clone()
         .or_else(|| std::env::var("OM_SESSION").ok());
-    let mut session = session_name.map(|name| Session::load(&name)).transpose()?;
+    let session_format = SessionFormat::resolve(args.session_format.as_deref())?;
+    let mut session = session_name
+        .map(|name| Session::load_with_format(&name, session_format))
+        .transpose()?;
 
     let format = if let Some(ref fmt) = args.format {
         fmt.parse::<OutputFormat>()?
@@ -23,14 +41,58 @@ pub fn run(args: CatArgs) -> Result<(), Box<dyn std::error::Error>> {
         OutputFormat::Text
     };
 
-    if args.files.is_empty() {
-        cat_by_level(&root, &args, &mut session, format)?;
+    if format == OutputFormat::JsonlPaths {
+        return Err(
+            "jsonl-paths format is only supported by tree; cat supports text, json, xml".into(),
+        );
+    }
+
+    // Validate up front; decoding happens per file.
+    Encoding::from_args(&args)?;
+
+    let scorer = Scorer {
+        case_insensitive_names: !args.case_sensitive_scores,
+        ..Scorer::default()
+    };
+
+    let emitted = if args.stdin_content {
+        cat_stdin(&root, &args, &mut session, format, &scorer)?
+    } else if args.files.is_empty() {
+        cat_by_level(&root, &args, &mut session, format, &scorer)?
     } else {
-        cat_files(&root, &args.files, &args, &mut session, format)?;
+        cat_files(&root, &args.files, &args, &mut session, format, &scorer)?
+    };
+
+    if let Some(ref log_path) = args.json_stream_to {
+        let params = serde_json::json!({
+            "files": args.files,
+            "level": args.level,
+            "top": args.top,
+            "budget": args.budget,
+            "session": session.as_ref().map(|s| &s.name),
+            "format": args.format,
+            "dirty": args.dirty,
+            "staged": args.staged,
+            "unstaged": args.unstaged,
+            "since": args.since,
+            "hunks": args.hunks,
+            "group_tests": args.group_tests,
+        });
+        let manifest = RunManifest::new(
+            "cat",
+            output::project_name(&root, args.project_name.as_deref()),
+            params,
+            emitted,
+        );
+        output::json::append_manifest(log_path, &manifest)?;
     }
 
-    if let Some(ref sess) = session {
-        sess.save()?;
+    // `--tokens-only` is a dry run: the session is neither marked nor saved.
+    if let Some(ref mut sess) = session {
+        if !args.tokens_only {
+            sess.repo_root = Some(fs::canonicalize(&root).unwrap_or(root));
+            sess.save()?;
+        }
     }
 
     Ok(())
@@ -41,12 +103,14 @@ fn cat_by_level(
     args: &CatArgs,
     session: &mut Option<Session>,
     format: OutputFormat,
-) -> Result<(), Box<dyn std::error::Error>> {
+    scorer: &Scorer,
+) -> Result<Vec<ManifestFile>, Box<dyn std::error::Error>> {
     let min_score = args.level.unwrap_or(5);
 
     let path = PathBuf::from(args.path.clone().unwrap_or_else(|| ".".to_string()));
 
     let files = git::ls_files(root)?;
+    let total_files = files.len();
     let ignore = IgnorePatterns::load(root);
 
     let git_status = if args.dirty || args.staged || args.unstaged {
@@ -55,6 +119,12 @@ fn cat_by_level(
         None
     };
 
+    let changed = args
+        .since
+        .as_deref()
+        .map(|since| git::changed_since(root, since))
+        .transpose()?;
+
     let filter_prefix = if args.git_root {
         None
     } else {
@@ -96,22 +166,23 @@ fn cat_by_level(
                 true
             }
         })
+        .filter(|p| changed.as_ref().is_none_or(|changed| changed.contains(p)))
         .collect();
 
-    let jobs = num_cpus::get();
-    let mut scored: Vec<ScoredFile> = if jobs > 1 {
-        use rayon::prelude::*;
-        file_strs
-            .par_iter()
-            .map(|f| crate::scorer::score_file(f))
-            .collect()
-    } else {
-        score_files(file_strs)
-    };
+    let pool = scorer::thread_pool(args.jobs)?;
+    let mut scored = pool.install(|| scorer.score_many(&file_strs));
     scored.retain(|f| f.score >= min_score);
     scored.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.cmp(&b.path)));
+    let diffs = select_hunks(root, args, &mut scored)?;
+
+    if scored.is_empty() {
+        output::report_no_files(total_files);
+        if format == OutputFormat::Text && !args.tokens_only {
+            return Ok(Vec::new());
+        }
+    }
 
-    output_files(root, &scored, args, session, format)
+    output_files(root, &scored, diffs, args, session, format, scorer)
 }
 
 fn cat_files(
@@ -120,11 +191,12 @@ fn cat_files(
     args: &CatArgs,
     session: &mut Option<Session>,
     format: OutputFormat,
-) -> Result<(), Box<dyn std::error::Error>> {
+    scorer: &Scorer,
+) -> Result<Vec<ManifestFile>, Box<dyn std::error::Error>> {
     let cwd = std::env::current_dir()?;
     let canonical_root = fs::canonicalize(root)?;
 
-    let scored: Vec<ScoredFile> = files
+    let mut scored: Vec<ScoredFile> = files
         .iter()
         .map(|f| {
             let abs_path = cwd.join(f);
@@ -141,154 +213,591 @@ fn cat_files(
             }
         })
         .collect();
+    let diffs = select_hunks(root, args, &mut scored)?;
+
+    output_files(root, &scored, diffs, args, session, format, scorer)
+}
+
+/// `--hunks`: diffs against HEAD of `files`, dropping the files that have
+/// none. Untracked files, and every file before the first commit, are new
+/// in full and stay selected.
+fn select_hunks(
+    root: &Path,
+    args: &CatArgs,
+    files: &mut Vec<ScoredFile>,
+) -> Result<HashMap<String, Vec<u8>>, Box<dyn std::error::Error>> {
+    if !args.hunks {
+        return Ok(HashMap::new());
+    }
+
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    let Some(diffs) = git::diffs_by_file(root, &paths, args.hunk_context)? else {
+        return Ok(HashMap::new());
+    };
+    let untracked = git::untracked_files(root)?;
+    files.retain(|f| diffs.contains_key(&f.path) || untracked.contains(&f.path));
+    Ok(diffs)
+}
+
+/// A file selected for output, with replacements already applied.
+struct CatFile {
+    path: String,
+    score: i32,
+    hash: String,
+    content: String,
+    tokens: Option<usize>,
+    group: Option<FileGroup>,
+}
+
+impl CatFile {
+    fn tokens(&mut self) -> usize {
+        let content = &self.content;
+        *self.tokens.get_or_insert_with(|| count_tokens(content))
+    }
 
-    output_files(root, &scored, args, session, format)
+    /// Content-free record of an emitted file for `--json-stream-to`.
+    fn manifest_entry(&self, lines: usize) -> ManifestFile {
+        ManifestFile {
+            path: self.path.clone(),
+            score: self.score,
+            tokens: self.tokens,
+            lines: Some(lines),
+            match_score: None,
+            group: self.group,
+        }
+    }
 }
 
 fn output_files(
     root: &Path,
     files: &[ScoredFile],
+    diffs: HashMap<String, Vec<u8>>,
     args: &CatArgs,
     session: &mut Option<Session>,
     format: OutputFormat,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let no_headers = args.no_headers;
-    let mut total_files = 0;
-    let mut skipped_binary = 0;
-    let mut skipped_session = 0;
-    let mut total_lines = 0;
-    let mut output_files_data = Vec::new();
+    scorer: &Scorer,
+) -> Result<Vec<ManifestFile>, Box<dyn std::error::Error>> {
+    let loader = Loader::new(root, args, diffs)?;
 
+    if format == OutputFormat::Text && args.template.is_none() && !args.tokens_only {
+        let stdout = io::stdout();
+        let (skipped, emitted) =
+            stream_text(&mut stdout.lock(), files, args, session, &loader, scorer)?;
+        report_errors(&skipped.errors);
+        return Ok(emitted);
+    }
+
+    let mut skipped = Skipped::default();
+    let mut output_files_data = Vec::new();
     for f in files {
+        if let Some(file) = loader.load(f, session.as_ref(), &mut skipped)? {
+            output_files_data.push(file);
+        }
+    }
+
+    emit_files(
+        root,
+        output_files_data,
+        skipped,
+        args,
+        session,
+        format,
+        scorer,
+    )
+}
+
+/// Per-run settings for reading selected files from the repository.
+struct Loader<'a> {
+    root: &'a Path,
+    args: &'a CatArgs,
+    redactor: Redactor,
+    encoding: Encoding,
+    /// `--hunks`: raw diffs against HEAD of the candidate files, by path.
+    diffs: HashMap<String, Vec<u8>>,
+}
+
+impl<'a> Loader<'a> {
+    fn new(
+        root: &'a Path,
+        args: &'a CatArgs,
+        diffs: HashMap<String, Vec<u8>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Loader {
+            root,
+            args,
+            redactor: Redactor::new(&args.replace, args.redact_secrets)?,
+            encoding: Encoding::from_args(args)?,
+            diffs,
+        })
+    }
+
+    /// Read one selected file and apply LFS resolution, session dedup, hunk
+    /// extraction and replacements. `None` means the file was skipped (and
+    /// counted in `skipped` where applicable).
+    fn load(
+        &self,
+        f: &ScoredFile,
+        session: Option<&Session>,
+        skipped: &mut Skipped,
+    ) -> Result<Option<CatFile>, Box<dyn std::error::Error>> {
+        let (root, args) = (self.root, self.args);
         let full_path = root.join(&f.path);
 
         if !full_path.exists() {
-            continue;
+            return Ok(None);
+        }
+
+        if !args.include_empty && is_empty_file(&full_path) {
+            skipped.empty += 1;
+            return Ok(None);
         }
 
         if !is_text_file(&full_path) {
-            skipped_binary += 1;
-            continue;
+            skipped.binary += 1;
+            return Ok(None);
         }
 
         let content = match fs::read(&full_path) {
             Ok(c) => c,
-            Err(_) => {
-                skipped_binary += 1;
-                continue;
+            Err(e) => {
+                skipped.errors.push(FileError {
+                    path: f.path.clone(),
+                    error: e.to_string(),
+                });
+                return Ok(None);
             }
         };
 
-        if let Some(ref sess) = session {
-            if !args.no_cache {
-                let hash = Session::compute_hash(&content);
-                if sess.was_read(&f.path, &hash) {
-                    skipped_session += 1;
-                    continue;
+        let content = if is_lfs_pointer(&content) {
+            if !args.lfs {
+                skipped.lfs += 1;
+                return Ok(None);
+            }
+            match git::lfs_smudge(root, &content) {
+                Ok(resolved) if resolved.len() <= MAX_TEXT_SIZE => resolved,
+                Ok(_) => {
+                    skipped.binary += 1;
+                    return Ok(None);
+                }
+                Err(_) => {
+                    skipped.lfs += 1;
+                    return Ok(None);
                 }
             }
+        } else {
+            content
+        };
+
+        // Session hashes use the original bytes so dedup stays stable
+        // regardless of which replacements are active.
+        let hash = Session::compute_hash(&content);
+
+        if let Some(sess) = session {
+            if !args.no_cache && sess.was_read(&f.path, &hash) {
+                skipped.session += 1;
+                return Ok(None);
+            }
+        }
+
+        let Some(mut text) = decode_reporting(self.encoding, &f.path, &content) else {
+            skipped.encoding += 1;
+            return Ok(None);
+        };
+
+        // Files without a diff against HEAD (untracked, or any file before
+        // the first commit) are emitted whole.
+        if let Some(diff) = self.diffs.get(&f.path) {
+            let Some((diff, _)) = self.encoding.decode(diff) else {
+                skipped.encoding += 1;
+                return Ok(None);
+            };
+            let hunks = git::parse_hunks(&diff);
+            if !hunks.is_empty() {
+                text = hunks
+                    .iter()
+                    .map(|h| h.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            }
         }
 
-        total_files += 1;
-        output_files_data.push((f.path.clone(), f.score, content));
+        Ok(Some(CatFile {
+            path: f.path.clone(),
+            score: f.score,
+            hash,
+            content: self.redactor.apply(&text),
+            tokens: None,
+            group: None,
+        }))
     }
+}
 
-    match format {
-        OutputFormat::Text => {
-            if !no_headers {
-                let project_name = root
-                    .file_name()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("project");
+/// Text output written file by file as each is read, so output starts
+/// immediately and contents are never all held in memory. Selection limits
+/// apply incrementally; files after a binding limit are not read at all.
+/// With `--group-tests`, admitted test files are held back until the source
+/// files have been written.
+fn stream_text<W: Write>(
+    out: &mut W,
+    files: &[ScoredFile],
+    args: &CatArgs,
+    session: &mut Option<Session>,
+    loader: &Loader,
+    scorer: &Scorer,
+) -> Result<(Skipped, Vec<ManifestFile>), Box<dyn std::error::Error>> {
+    let root = loader.root;
+    let budgets = DirBudgets::load(root);
+    let mut selector = Selector::new(&budgets, args);
+    let mut skipped = Skipped::default();
+    let mut emitted = Vec::new();
+    let mut binding_limit = None;
+    let mut files_shown = 0;
+    let mut total_lines = 0;
+    let mut in_tests = false;
+    let mut deferred_tests = Vec::new();
+
+    if !args.no_headers {
+        write_text_preamble(out, root, session, args)?;
+        out.flush()?;
+    }
 
-                println!("# Project: {}", project_name);
+    for f in files {
+        let Some(mut file) = loader.load(f, session.as_ref(), &mut skipped)? else {
+            continue;
+        };
 
-                if let Some(ref sess) = session {
-                    println!("# Session: {}", sess.name);
-                }
+        match selector.admit(&mut file, &mut skipped) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(limit) => {
+                binding_limit = Some(limit);
+                break;
+            }
+        }
 
-                println!("# Files: {} shown", total_files);
+        file.group = group_of(&file.path, args, scorer);
+        if file.group == Some(FileGroup::Test) {
+            deferred_tests.push(file);
+            continue;
+        }
 
-                if skipped_binary > 0 {
-                    println!("# Skipped: {} binary/unreadable", skipped_binary);
-                }
+        let line_count = write_streamed_file(out, &mut file, args, session, &mut in_tests)?;
+        total_lines += line_count;
+        files_shown += 1;
+        emitted.push(file.manifest_entry(line_count));
+    }
 
-                if skipped_session > 0 {
-                    println!("# Skipped: {} unchanged (session)", skipped_session);
-                }
+    for mut file in deferred_tests {
+        let line_count = write_streamed_file(out, &mut file, args, session, &mut in_tests)?;
+        total_lines += line_count;
+        files_shown += 1;
+        emitted.push(file.manifest_entry(line_count));
+    }
+
+    if !args.no_headers {
+        write_text_summary(out, files_shown, &skipped, binding_limit, total_lines, args)?;
+    }
+    out.flush()?;
+
+    Ok((skipped, emitted))
+}
+
+/// Write one file of streamed output, flush it, and mark it read.
+fn write_streamed_file<W: Write>(
+    out: &mut W,
+    file: &mut CatFile,
+    args: &CatArgs,
+    session: &mut Option<Session>,
+    in_tests: &mut bool,
+) -> io::Result<usize> {
+    write_group_divider(out, file, in_tests)?;
+    let line_count = write_text_file(out, file, args)?;
+    out.flush()?;
+
+    if let Some(ref mut sess) = session {
+        if !args.no_cache {
+            sess.mark_read(&file.path, &file.hash);
+        }
+    }
+
+    Ok(line_count)
+}
+
+/// `--group-tests` section for `path`, if grouping is on.
+fn group_of(path: &str, args: &CatArgs, scorer: &Scorer) -> Option<FileGroup> {
+    args.group_tests
+        .then(|| FileGroup::new(scorer.is_test(path)))
+}
+
+/// Emit stdin-provided buffers (see `framed`) without touching the
+/// filesystem or git. Paths are scored as if they were in a repository.
+fn cat_stdin(
+    root: &Path,
+    args: &CatArgs,
+    session: &mut Option<Session>,
+    format: OutputFormat,
+    scorer: &Scorer,
+) -> Result<Vec<ManifestFile>, Box<dyn std::error::Error>> {
+    let redactor = Redactor::new(&args.replace, args.redact_secrets)?;
+    let encoding = Encoding::from_args(args)?;
+    let entries = framed::read_entries(std::io::stdin().lock())?;
+    let mut skipped = Skipped::default();
+
+    let mut output_files_data: Vec<CatFile> = Vec::new();
+    for entry in entries {
+        if !args.include_empty && entry.content.is_empty() {
+            skipped.empty += 1;
+            continue;
+        }
+
+        let hash = Session::compute_hash(&entry.content);
+
+        if let Some(ref sess) = session {
+            if !args.no_cache && sess.was_read(&entry.path, &hash) {
+                skipped.session += 1;
+                continue;
             }
+        }
 
-            for (path, _score, content) in &output_files_data {
-                let content_str = String::from_utf8_lossy(content);
-                let line_count = content_str.lines().count();
-                total_lines += line_count;
+        let Some(text) = decode_reporting(encoding, &entry.path, &entry.content) else {
+            skipped.encoding += 1;
+            continue;
+        };
 
-                let hash = Session::compute_hash(content);
-                let hash_prefix = &hash[..12];
+        let scored = scorer.score(&entry.path);
+        output_files_data.push(CatFile {
+            path: scored.path,
+            score: scored.score,
+            hash,
+            content: redactor.apply(&text),
+            tokens: None,
+            group: None,
+        });
+    }
+    output_files_data.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.cmp(&b.path)));
 
-                let mut header = format!("FILE: {}\nLINES: {}", path, line_count);
-                if args.tokens {
-                    let tokens = crate::tokens::count_tokens(&content_str, "cl100k_base")
-                        .unwrap_or(content_str.len() / 4);
-                    header.push_str(&format!("\nTOKENS: {}", tokens));
-                }
-                header.push_str(&format!("\nHASH: {}", hash_prefix));
+    emit_files(
+        root,
+        output_files_data,
+        skipped,
+        args,
+        session,
+        format,
+        scorer,
+    )
+}
+
+#[derive(Debug, Default)]
+struct Skipped {
+    binary: usize,
+    session: usize,
+    lfs: usize,
+    empty: usize,
+    /// Invalid UTF-8 under `--encoding utf8`.
+    encoding: usize,
+    /// Files over their `.ombudget` directory cap, and those prefixes.
+    budget: usize,
+    budget_prefixes: BTreeSet<String>,
+    /// Genuine IO failures, as opposed to files skipped by design.
+    errors: Vec<FileError>,
+}
+
+/// Running `--top`/`--budget` state: files taken so far and their tokens.
+struct Limits {
+    top: Option<usize>,
+    budget: Option<usize>,
+    selected: usize,
+    used: usize,
+}
+
+impl Limits {
+    fn new(top: Option<usize>, budget: Option<usize>) -> Self {
+        Limits {
+            top,
+            budget,
+            selected: 0,
+            used: 0,
+        }
+    }
+
+    /// Take the next file, or return the limit that stops selection.
+    fn take(&mut self, tokens: usize) -> Result<(), BindingLimit> {
+        if self.top.is_some_and(|top| self.selected >= top) {
+            return Err(BindingLimit::Top);
+        }
+        if self
+            .budget
+            .is_some_and(|budget| self.used + tokens > budget)
+        {
+            return Err(BindingLimit::Budget);
+        }
+        self.selected += 1;
+        self.used += tokens;
+        Ok(())
+    }
+}
+
+/// Applies `.ombudget` caps, then `--top`/`--budget`, to files in output
+/// order. Tokens are only counted when a cap or `--budget` needs them.
+struct Selector<'a> {
+    dir_budgets: BudgetTracker<'a>,
+    limits: Limits,
+}
+
+impl<'a> Selector<'a> {
+    fn new(budgets: &'a DirBudgets, args: &CatArgs) -> Self {
+        Selector {
+            dir_budgets: BudgetTracker::new(budgets),
+            limits: Limits::new(args.top, args.budget),
+        }
+    }
+
+    /// Whether to emit `file`; `Err` means no further files should be.
+    fn admit(&mut self, file: &mut CatFile, skipped: &mut Skipped) -> Result<bool, BindingLimit> {
+        let path = file.path.clone();
+        if let Err(prefix) = self.dir_budgets.admit(&path, || file.tokens()) {
+            skipped.budget += 1;
+            skipped.budget_prefixes.insert(format!("{}/", prefix));
+            return Ok(false);
+        }
+
+        let tokens = if self.limits.budget.is_some() {
+            file.tokens()
+        } else {
+            0
+        };
+        self.limits.take(tokens)?;
+        Ok(true)
+    }
+}
+
+fn emit_files(
+    root: &Path,
+    output_files_data: Vec<CatFile>,
+    mut skipped: Skipped,
+    args: &CatArgs,
+    session: &mut Option<Session>,
+    format: OutputFormat,
+    scorer: &Scorer,
+) -> Result<Vec<ManifestFile>, Box<dyn std::error::Error>> {
+    let template = args
+        .template
+        .as_ref()
+        .map(|path| {
+            fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read template {}: {}", path.display(), e))
+        })
+        .transpose()?;
+    let no_headers = args.no_headers;
+    let mut total_lines = 0;
+
+    let budgets = DirBudgets::load(root);
+    let mut selector = Selector::new(&budgets, args);
+    let mut binding_limit = None;
+    let mut selected = Vec::with_capacity(output_files_data.len());
+    for mut file in output_files_data {
+        match selector.admit(&mut file, &mut skipped) {
+            Ok(true) => selected.push(file),
+            Ok(false) => {}
+            Err(limit) => {
+                binding_limit = Some(limit);
+                break;
+            }
+        }
+    }
+    let mut output_files_data = selected;
 
-                println!("\n{}", "=".repeat(80));
-                println!("{}", header);
-                println!("{}", "=".repeat(80));
-                println!("{}", content_str);
+    // Group only what was selected, so --group-tests never changes which
+    // files are picked. Stable, so each group keeps score order.
+    if args.group_tests {
+        for file in &mut output_files_data {
+            file.group = group_of(&file.path, args, scorer);
+        }
+        output_files_data.sort_by_key(|f| f.group == Some(FileGroup::Test));
+    }
+
+    // Dry run for budgeting: nothing is printed or marked read in the session.
+    if args.tokens_only {
+        let mut total_tokens = 0;
+        for file in &mut output_files_data {
+            let tokens = file.tokens();
+            if args.verbose {
+                eprintln!("{:>8} {}", tokens, file.path);
+            }
+            total_tokens += tokens;
+        }
+        println!("{}", total_tokens);
+        report_errors(&skipped.errors);
+        return Ok(output_files_data
+            .iter()
+            .map(|f| f.manifest_entry(f.content.lines().count()))
+            .collect());
+    }
+
+    let total_files = output_files_data.len();
+    let mut emitted = Vec::with_capacity(total_files);
+
+    match format {
+        OutputFormat::Text if template.is_none() => {
+            let stdout = io::stdout();
+            let mut out = stdout.lock();
+
+            if !no_headers {
+                write_text_preamble(&mut out, root, session, args)?;
+                write_text_counts(&mut out, total_files, &skipped, binding_limit, args)?;
+            }
+
+            let mut in_tests = false;
+            for file in &mut output_files_data {
+                write_group_divider(&mut out, file, &mut in_tests)?;
+                let line_count = write_text_file(&mut out, file, args)?;
+                total_lines += line_count;
+                emitted.push(file.manifest_entry(line_count));
 
                 if let Some(ref mut sess) = session {
                     if !args.no_cache {
-                        sess.mark_read(path, &hash);
+                        sess.mark_read(&file.path, &file.hash);
                     }
                 }
             }
 
             if !no_headers && total_files > 0 {
-                println!("\n# Total lines: {}", total_lines);
+                writeln!(out, "\n# Total lines: {}", total_lines)?;
             }
         }
-        OutputFormat::Json | OutputFormat::Xml => {
+        OutputFormat::JsonlPaths => unreachable!(),
+        OutputFormat::Text | OutputFormat::Json | OutputFormat::Xml => {
             let mut file_outputs = Vec::new();
 
-            for (path, score, content) in &output_files_data {
-                let content_str = String::from_utf8_lossy(content);
-                let line_count = content_str.lines().count();
+            for mut file in output_files_data {
+                let line_count = file.content.lines().count();
                 total_lines += line_count;
 
-                let hash = Session::compute_hash(content);
-
-                let tokens = if args.tokens {
-                    Some(
-                        crate::tokens::count_tokens(&content_str, "cl100k_base")
-                            .unwrap_or(content_str.len() / 4),
-                    )
+                let tokens = if args.tokens || template.is_some() {
+                    Some(file.tokens())
                 } else {
                     None
                 };
 
-                file_outputs.push(FileOutput {
-                    path: path.clone(),
-                    score: *score,
-                    tokens,
-                    lines: line_count,
-                    content: Some(content_str.to_string()),
-                });
-
                 if let Some(ref mut sess) = session {
                     if !args.no_cache {
-                        sess.mark_read(path, &hash);
+                        sess.mark_read(&file.path, &file.hash);
                     }
                 }
+
+                emitted.push(file.manifest_entry(line_count));
+                file_outputs.push(FileOutput {
+                    path: file.path,
+                    score: file.score,
+                    tokens,
+                    lines: line_count,
+                    content: Some(file.content),
+                    match_score: None,
+                    group: file.group,
+                });
             }
 
-            let project_name = root
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("project")
-                .to_string();
+            let project_name = output::project_name(root, args.project_name.as_deref());
 
             let session_name = session.as_ref().map(|s| s.name.clone());
 
@@ -296,23 +805,283 @@ fn output_files(
                 project: project_name,
                 session: session_name,
                 files_shown: total_files,
-                skipped_binary,
-                skipped_session,
+                skipped_binary: skipped.binary,
+                skipped_session: skipped.session,
+                skipped_lfs: skipped.lfs,
+                skipped_empty: skipped.empty,
+                skipped_encoding: skipped.encoding,
+                skipped_budget: skipped.budget,
+                budget_exceeded: skipped.budget_prefixes.iter().cloned().collect(),
                 total_lines,
+                binding_limit,
+                errors: skipped.errors.clone(),
                 files: file_outputs,
             };
 
-            match format {
-                OutputFormat::Json => output::json::output_cat(&cat_output)?,
-                OutputFormat::Xml => output::xml::output_cat(&cat_output)?,
-                OutputFormat::Text => unreachable!(),
+            match (&template, format) {
+                (Some(template), _) => output::template::output_cat(template, &cat_output)?,
+                (None, OutputFormat::Json) => output::json::output_cat(&cat_output)?,
+                (None, OutputFormat::Xml) => output::xml::output_cat(&cat_output)?,
+                (None, OutputFormat::Text | OutputFormat::JsonlPaths) => unreachable!(),
             }
         }
     }
 
+    report_errors(&skipped.errors);
+
+    Ok(emitted)
+}
+
+fn report_errors(errors: &[FileError]) {
+    if !errors.is_empty() {
+        eprintln!("warning: {} file(s) could not be read:", errors.len());
+        for e in errors {
+            eprintln!("  {}: {}", e.path, e.error);
+        }
+    }
+}
+
+fn write_text_preamble<W: Write>(
+    out: &mut W,
+    root: &Path,
+    session: &Option<Session>,
+    args: &CatArgs,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "# Project: {}",
+        output::project_name(root, args.project_name.as_deref())
+    )?;
+
+    if let Some(ref sess) = session {
+        writeln!(out, "# Session: {}", sess.name)?;
+    }
+
+    Ok(())
+}
+
+/// Under `--group-tests`, mark where the test section begins.
+fn write_group_divider<W: Write>(
+    out: &mut W,
+    file: &CatFile,
+    in_tests: &mut bool,
+) -> io::Result<()> {
+    if file.group == Some(FileGroup::Test) && !*in_tests {
+        *in_tests = true;
+        writeln!(out, "\n# Tests")?;
+    }
+    Ok(())
+}
+
+/// Write one file block and return its line count.
+fn write_text_file<W: Write>(out: &mut W, file: &mut CatFile, args: &CatArgs) -> io::Result<usize> {
+    let line_count = file.content.lines().count();
+
+    if args.file_prefix.is_some() || args.file_suffix.is_some() {
+        let render = |template: &str| {
+            render_file_template(template, &file.path, file.score, line_count, &file.content)
+        };
+        if let Some(ref prefix) = args.file_prefix {
+            writeln!(out, "{}", render(prefix))?;
+        }
+        writeln!(out, "{}", file.content)?;
+        if let Some(ref suffix) = args.file_suffix {
+            writeln!(out, "{}", render(suffix))?;
+        }
+    } else {
+        let mut header = format!("FILE: {}\nLINES: {}", file.path, line_count);
+        if args.tokens {
+            header.push_str(&format!("\nTOKENS: {}", file.tokens()));
+        }
+        header.push_str(&format!("\nHASH: {}", &file.hash[..12]));
+
+        writeln!(out, "\n{}", "=".repeat(80))?;
+        writeln!(out, "{}", header)?;
+        writeln!(out, "{}", "=".repeat(80))?;
+        writeln!(out, "{}", file.content)?;
+    }
+
+    Ok(line_count)
+}
+
+/// Streamed output only knows its counts once every file has been
+/// considered, so they follow the files instead of leading them.
+fn write_text_summary<W: Write>(
+    out: &mut W,
+    files_shown: usize,
+    skipped: &Skipped,
+    binding_limit: Option<BindingLimit>,
+    total_lines: usize,
+    args: &CatArgs,
+) -> io::Result<()> {
+    if files_shown > 0 {
+        writeln!(out)?;
+    }
+
+    write_text_counts(out, files_shown, skipped, binding_limit, args)?;
+
+    if files_shown > 0 {
+        writeln!(out, "# Total lines: {}", total_lines)?;
+    }
+
+    Ok(())
+}
+
+/// The `# Files`, `# Skipped` and `# Limit` lines.
+fn write_text_counts<W: Write>(
+    out: &mut W,
+    files_shown: usize,
+    skipped: &Skipped,
+    binding_limit: Option<BindingLimit>,
+    args: &CatArgs,
+) -> io::Result<()> {
+    writeln!(out, "# Files: {} shown", files_shown)?;
+
+    if skipped.binary > 0 {
+        writeln!(out, "# Skipped: {} binary/unreadable", skipped.binary)?;
+    }
+
+    if skipped.session > 0 {
+        writeln!(out, "# Skipped: {} unchanged (session)", skipped.session)?;
+    }
+
+    if skipped.lfs > 0 {
+        writeln!(out, "# Skipped: {} git-lfs pointers", skipped.lfs)?;
+    }
+
+    if skipped.empty > 0 {
+        writeln!(out, "# Skipped: {} empty", skipped.empty)?;
+    }
+
+    if skipped.encoding > 0 {
+        writeln!(out, "# Skipped: {} invalid UTF-8", skipped.encoding)?;
+    }
+
+    if skipped.budget > 0 {
+        let prefixes: Vec<&str> = skipped.budget_prefixes.iter().map(String::as_str).collect();
+        writeln!(
+            out,
+            "# Skipped: {} over directory budget ({})",
+            skipped.budget,
+            prefixes.join(", ")
+        )?;
+    }
+
+    match binding_limit {
+        Some(BindingLimit::Top) => {
+            writeln!(out, "# Limit: --top {} reached", args.top.unwrap_or(0))?
+        }
+        Some(BindingLimit::Budget) => writeln!(
+            out,
+            "# Limit: --budget {} tokens reached",
+            args.budget.unwrap_or(0)
+        )?,
+        None => {}
+    }
+
     Ok(())
 }
 
+fn count_tokens(text: &str) -> usize {
+    crate::tokens::count_tokens(text, "cl100k_base").unwrap_or(text.len() / 4)
+}
+
+/// Expand `{path}`, `{score}`, `{lines}` and `{tokens}` in a per-file
+/// wrapper template. Tokens are only counted when the template asks for them.
+fn render_file_template(
+    template: &str,
+    path: &str,
+    score: i32,
+    lines: usize,
+    content: &str,
+) -> String {
+    let mut rendered = template
+        .replace("{path}", path)
+        .replace("{score}", &score.to_string())
+        .replace("{lines}", &lines.to_string());
+
+    if rendered.contains("{tokens}") {
+        rendered = rendered.replace("{tokens}", &count_tokens(content).to_string());
+    }
+
+    rendered
+}
+
+/// How file bytes become text (`--encoding`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Encoding {
+    /// Strict UTF-8; files with invalid sequences are skipped.
+    Utf8,
+    /// UTF-8 with invalid sequences replaced by U+FFFD.
+    #[default]
+    Utf8Lossy,
+    /// ISO-8859-1: every byte maps to the code point of the same value.
+    Latin1,
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utf8" | "utf-8" => Ok(Encoding::Utf8),
+            "utf8-lossy" | "utf-8-lossy" => Ok(Encoding::Utf8Lossy),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(Encoding::Latin1),
+            _ => Err(format!(
+                "Invalid encoding: {}. Use utf8, utf8-lossy, or latin1",
+                s
+            )),
+        }
+    }
+}
+
+impl Encoding {
+    fn from_args(args: &CatArgs) -> Result<Self, String> {
+        args.encoding
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    /// Decode `bytes`, or `None` if strict UTF-8 decoding fails. The flag
+    /// reports whether invalid bytes were replaced.
+    fn decode(self, bytes: &[u8]) -> Option<(String, bool)> {
+        match self {
+            Encoding::Utf8 => std::str::from_utf8(bytes)
+                .ok()
+                .map(|text| (text.to_string(), false)),
+            Encoding::Utf8Lossy => {
+                let text = String::from_utf8_lossy(bytes);
+                let replaced = matches!(text, Cow::Owned(_));
+                Some((text.into_owned(), replaced))
+            }
+            Encoding::Latin1 => Some((bytes.iter().map(|&b| b as char).collect(), false)),
+        }
+    }
+}
+
+/// `Encoding::decode`, warning on stderr when content was altered.
+fn decode_reporting(encoding: Encoding, path: &str, bytes: &[u8]) -> Option<String> {
+    let (text, replaced) = encoding.decode(bytes)?;
+    if replaced {
+        eprintln!(
+            "warning: {}: invalid UTF-8 replaced with U+FFFD (see --encoding)",
+            path
+        );
+    }
+    Some(text)
+}
+
+const MAX_TEXT_SIZE: usize = 200_000;
+
+const LFS_POINTER_PREFIX: &[u8] = b"version https://git-lfs.github.com/spec/";
+
+/// Git LFS pointer files are small stubs standing in for the real object.
+fn is_lfs_pointer(content: &[u8]) -> bool {
+    content.starts_with(LFS_POINTER_PREFIX)
+}
+
 fn is_text_file(path: &Path) -> bool {
     let mime = mime_guess::from_path(path).first_or_octet_stream();
     use mime_guess::mime;
@@ -328,7 +1097,7 @@ fn is_text_file(path: &Path) -> bool {
     }
 
     if let Ok(metadata) = fs::metadata(path) {
-        if metadata.len() > 200_000 {
+        if metadata.len() > MAX_TEXT_SIZE as u64 {
             return false;
         }
     }
@@ -362,6 +1131,26 @@ mod tests {
     use std::io::Write;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_encoding_decode() {
+        let latin1 = b"caf\xe9";
+        assert_eq!(Encoding::Utf8.decode(latin1), None);
+        assert_eq!(
+            Encoding::Utf8Lossy.decode(latin1),
+            Some(("caf\u{fffd}".to_string(), true))
+        );
+        assert_eq!(
+            Encoding::Latin1.decode(latin1),
+            Some(("café".to_string(), false))
+        );
+        assert_eq!(
+            Encoding::Utf8Lossy.decode("café".as_bytes()),
+            Some(("café".to_string(), false))
+        );
+        assert_eq!("UTF-8".parse::<Encoding>(), Ok(Encoding::Utf8));
+        assert!("utf16".parse::<Encoding>().is_err());
+    }
+
     #[test]
     fn test_is_text_file() {
         assert!(is_text_file(Path::new("src/main.rs")));
@@ -390,4 +1179,175 @@ mod tests {
         }
         assert!(is_text_file(&small_txt));
     }
+
+    #[test]
+    fn test_render_file_template() {
+        assert_eq!(
+            render_file_template(
+                "--- BEGIN {path} ({score}, {lines} lines) ---",
+                "src/a.rs",
+                9,
+                3,
+                ""
+            ),
+            "--- BEGIN src/a.rs (9, 3 lines) ---"
+        );
+        let tokens = render_file_template("{tokens}", "a.rs", 9, 1, "hello world");
+        assert!(tokens.parse::<usize>().unwrap() > 0);
+        assert_eq!(
+            render_file_template("{unknown}", "a.rs", 9, 1, ""),
+            "{unknown}"
+        );
+    }
+
+    #[test]
+    fn test_is_lfs_pointer() {
+        let pointer = b"version https://git-lfs.github.com/spec/v1\noid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\nsize 12345\n";
+        assert!(is_lfs_pointer(pointer));
+        assert!(!is_lfs_pointer(b"fn main() {}\n"));
+        assert!(!is_lfs_pointer(
+            b"# version https://git-lfs.github.com/spec/v1"
+        ));
+    }
+
+    /// Batch form of `Limits`: how many of `tokens` are selected and
+    /// which limit stopped the selection, if any.
+    fn select_within_limits(
+        tokens: impl Iterator<Item = usize>,
+        top: Option<usize>,
+        budget: Option<usize>,
+    ) -> (usize, Option<BindingLimit>) {
+        let mut limits = Limits::new(top, budget);
+        for file_tokens in tokens {
+            if let Err(limit) = limits.take(file_tokens) {
+                return (limits.selected, Some(limit));
+            }
+        }
+        (limits.selected, None)
+    }
+
+    #[test]
+    fn test_select_top_binds_first() {
+        let tokens = [100, 100, 100, 100];
+        assert_eq!(
+            select_within_limits(tokens.into_iter(), Some(2), Some(1_000)),
+            (2, Some(BindingLimit::Top))
+        );
+    }
+
+    #[test]
+    fn test_select_budget_binds_first() {
+        let tokens = [100, 100, 100, 100];
+        assert_eq!(
+            select_within_limits(tokens.into_iter(), Some(3), Some(250)),
+            (2, Some(BindingLimit::Budget))
+        );
+        // A file larger than the whole budget stops selection immediately.
+        assert_eq!(
+            select_within_limits([500, 10].into_iter(), None, Some(100)),
+            (0, Some(BindingLimit::Budget))
+        );
+    }
+
+    #[test]
+    fn test_select_no_limit_binds() {
+        let tokens = [100, 100];
+        assert_eq!(
+            select_within_limits(tokens.into_iter(), Some(5), Some(1_000)),
+            (2, None)
+        );
+        assert_eq!(
+            select_within_limits(tokens.into_iter(), Some(2), Some(200)),
+            (2, None)
+        );
+        assert_eq!(
+            select_within_limits(tokens.into_iter(), None, None),
+            (2, None)
+        );
+    }
+
+    /// Deletes `victim` as soon as `trigger` has been written.
+    struct DeleteOnWrite {
+        written: Vec<u8>,
+        trigger: &'static str,
+        victim: PathBuf,
+    }
+
+    impl Write for DeleteOnWrite {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            if String::from_utf8_lossy(&self.written).contains(self.trigger) {
+                let _ = fs::remove_file(&self.victim);
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_stream_text_writes_before_reading_later_files() {
+        use clap::Parser;
+
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), "alpha\n").unwrap();
+        fs::write(dir.path().join("b.rs"), "beta\n").unwrap();
+
+        let files: Vec<ScoredFile> = ["a.rs", "b.rs"]
+            .iter()
+            .map(|path| ScoredFile {
+                path: path.to_string(),
+                score: 10,
+                reason: "explicit".to_string(),
+            })
+            .collect();
+        let args = CatArgs::parse_from(["cat"]);
+        let loader = Loader::new(dir.path(), &args, HashMap::new()).unwrap();
+        let mut out = DeleteOnWrite {
+            written: Vec::new(),
+            trigger: "alpha",
+            victim: dir.path().join("b.rs"),
+        };
+
+        // If a.rs were not written before b.rs is read, b.rs would still
+        // exist when read and appear in the output.
+        stream_text(
+            &mut out,
+            &files,
+            &args,
+            &mut None,
+            &loader,
+            &Scorer::default(),
+        )
+        .unwrap();
+
+        let written = String::from_utf8(out.written).unwrap();
+        assert!(written.contains("FILE: a.rs"));
+        assert!(!written.contains("b.rs"));
+        assert!(written.contains("# Files: 1 shown"));
+    }
+
+    #[test]
+    fn test_load_reports_read_errors() {
+        use clap::Parser;
+
+        // Reading a directory fails regardless of privileges.
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("listed.rs")).unwrap();
+        let file = ScoredFile {
+            path: "listed.rs".to_string(),
+            score: 10,
+            reason: "explicit".to_string(),
+        };
+        let args = CatArgs::parse_from(["cat"]);
+        let loader = Loader::new(dir.path(), &args, HashMap::new()).unwrap();
+        let mut skipped = Skipped::default();
+
+        assert!(loader.load(&file, None, &mut skipped).unwrap().is_none());
+        assert_eq!(skipped.binary, 0);
+        assert_eq!(skipped.errors.len(), 1);
+        assert_eq!(skipped.errors[0].path, "listed.rs");
+    }
 }
@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "om")]
@@ -19,6 +20,9 @@ pub enum Commands {
 
     #[command(about = "Manage sessions")]
     Session(SessionArgs),
+
+    #[command(about = "Create a starter .omignore (or .om.toml with --scoring)")]
+    Init(InitArgs),
 }
 
 #[derive(Parser)]
@@ -29,6 +33,7 @@ pub struct TreeArgs {
     #[arg(
         short = 'j',
         long,
+        visible_alias = "max-concurrency",
         default_value_t = 0,
         help = "Number of parallel jobs (0 = auto)"
     )]
@@ -43,6 +48,20 @@ pub struct TreeArgs {
     #[arg(short, long, help = "Flat output instead of tree")]
     pub flat: bool,
 
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Group files under their N-level ancestor directory, flat within each group"
+    )]
+    pub flat_depth: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "In flat text output, elide the middle of paths deeper than N components"
+    )]
+    pub max_path_depth_display: Option<usize>,
+
     #[arg(long, help = "Disable colors")]
     pub no_color: bool,
 
@@ -61,11 +80,64 @@ pub struct TreeArgs {
     #[arg(long, help = "Show only unstaged files")]
     pub unstaged: bool,
 
-    #[arg(long, help = "Output format: text, json, xml (default: text)")]
+    #[arg(
+        long,
+        value_name = "REF",
+        help = "Only files changed since REF (commits plus working tree changes)"
+    )]
+    pub since: Option<String>,
+
+    #[arg(
+        long,
+        help = "Output format: text, json, xml, jsonl-paths (default: text)"
+    )]
     pub format: Option<String>,
 
     #[arg(short, long, help = "Show token counts")]
     pub tokens: bool,
+
+    #[arg(long, help = "Show a histogram of file counts per score")]
+    pub histogram: bool,
+
+    #[arg(
+        long,
+        value_name = "max|avg",
+        help = "Annotate directories with the max or average score of their files"
+    )]
+    pub dir_scores: Option<String>,
+
+    #[arg(long, help = "Include empty (zero-byte) files")]
+    pub include_empty: bool,
+
+    #[arg(long, help = "List test files in their own section after source files")]
+    pub group_tests: bool,
+
+    #[arg(
+        long,
+        value_name = "QUERY",
+        help = "Show only files whose name fuzzy-matches QUERY, best matches first"
+    )]
+    pub find: Option<String>,
+
+    #[arg(
+        long,
+        help = "Match special filenames (README, Makefile, ...) case-sensitively"
+    )]
+    pub case_sensitive_scores: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Append a JSON line describing this run (time, parameters, files) to PATH"
+    )]
+    pub json_stream_to: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Project name for output (default: repository directory name)"
+    )]
+    pub project_name: Option<String>,
 }
 
 #[derive(Parser)]
@@ -76,9 +148,32 @@ pub struct CatArgs {
     #[arg(short = 'l', long, help = "Minimum score level (1-10, default: 5)")]
     pub level: Option<i32>,
 
+    #[arg(
+        short = 'j',
+        long,
+        visible_alias = "max-concurrency",
+        default_value_t = 0,
+        help = "Number of parallel jobs (0 = auto)"
+    )]
+    pub jobs: usize,
+
     #[arg(short, long, help = "Project path (default: current directory)")]
     pub path: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Emit at most N files, highest score first"
+    )]
+    pub top: Option<usize>,
+
+    #[arg(
+        long,
+        value_name = "TOKENS",
+        help = "Stop before the total token count would exceed TOKENS"
+    )]
+    pub budget: Option<usize>,
+
     #[arg(long, help = "Disable headers")]
     pub no_headers: bool,
 
@@ -100,20 +195,165 @@ pub struct CatArgs {
     #[arg(long, help = "Show only unstaged files")]
     pub unstaged: bool,
 
+    #[arg(
+        long,
+        value_name = "REF",
+        help = "Only files changed since REF (commits plus working tree changes)"
+    )]
+    pub since: Option<String>,
+
     #[arg(long, help = "Output format: text, json, xml (default: text)")]
     pub format: Option<String>,
 
+    #[arg(
+        long,
+        value_name = "ENCODING",
+        help = "File encoding: utf8 (skip invalid), utf8-lossy, latin1 (default: utf8-lossy)"
+    )]
+    pub encoding: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Render output through a TinyTemplate file instead of --format"
+    )]
+    pub template: Option<PathBuf>,
+
     #[arg(short, long, help = "Show token counts")]
     pub tokens: bool,
 
     #[arg(long, help = "Disable session caching (deduplication)")]
     pub no_cache: bool,
+
+    #[arg(
+        long,
+        help = "Print only the total token count of the selected files, without content"
+    )]
+    pub tokens_only: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "With --tokens-only, list per-file token counts on stderr"
+    )]
+    pub verbose: bool,
+
+    #[arg(
+        long,
+        value_name = "PATTERN=REPLACEMENT",
+        help = "Replace regex matches in file content before output (repeatable)"
+    )]
+    pub replace: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Redact common secret formats (API keys, tokens, private keys)"
+    )]
+    pub redact_secrets: bool,
+
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        allow_hyphen_values = true,
+        help = "Text before each file, replacing the default header ({path}, {score}, {lines}, {tokens})"
+    )]
+    pub file_prefix: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "TEMPLATE",
+        allow_hyphen_values = true,
+        help = "Text after each file ({path}, {score}, {lines}, {tokens})"
+    )]
+    pub file_suffix: Option<String>,
+
+    #[arg(
+        long,
+        help = "Resolve git-lfs pointer files via `git lfs smudge` instead of skipping them"
+    )]
+    pub lfs: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "files", "level", "dirty", "staged", "unstaged", "since", "git_root", "lfs"
+        ],
+        help = "Read framed path/content pairs from stdin instead of the repository"
+    )]
+    pub stdin_content: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "stdin_content",
+        help = "Emit only the changed hunks of each file (git diff against HEAD)"
+    )]
+    pub hunks: bool,
+
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 3,
+        help = "Lines of context around each hunk with --hunks"
+    )]
+    pub hunk_context: usize,
+
+    #[arg(long, help = "Include empty (zero-byte) files")]
+    pub include_empty: bool,
+
+    #[arg(long, help = "List test files in their own section after source files")]
+    pub group_tests: bool,
+
+    #[arg(
+        long,
+        help = "Match special filenames (README, Makefile, ...) case-sensitively"
+    )]
+    pub case_sensitive_scores: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Append a JSON line describing this run (time, parameters, files) to PATH"
+    )]
+    pub json_stream_to: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "Project name for output (default: repository directory name)"
+    )]
+    pub project_name: Option<String>,
+
+    /// Session file format from config (`session_format`); not a flag.
+    #[arg(skip)]
+    pub session_format: Option<String>,
 }
 
 #[derive(Parser)]
 pub struct SessionArgs {
     #[command(subcommand)]
     pub command: Option<SessionCommand>,
+
+    /// Session file format from config (`session_format`); not a flag.
+    #[arg(skip)]
+    pub session_format: Option<String>,
+}
+
+#[derive(Parser)]
+pub struct InitArgs {
+    #[arg(
+        long,
+        help = "Write a commented .om.toml documenting scoring and filter options"
+    )]
+    pub scoring: bool,
+
+    #[arg(
+        long,
+        help = "Write to the home directory (~/.omignore or ~/.om/config.toml)"
+    )]
+    pub global: bool,
+
+    #[arg(long, help = "Overwrite an existing file")]
+    pub force: bool,
 }
 
 #[derive(Subcommand)]
@@ -123,4 +363,7 @@ pub enum SessionCommand {
         #[arg(help = "Session name")]
         name: String,
     },
+
+    #[command(about = "Remove sessions whose repository no longer exists")]
+    Gc,
 }
@@ -12,6 +12,8 @@ pub struct Config {
     pub level: Option<i32>,
     pub no_headers: Option<bool>,
     pub no_cache: Option<bool>,
+    pub session_format: Option<String>,
+    pub case_sensitive_scores: Option<bool>,
 }
 
 impl Config {
@@ -40,6 +42,12 @@ impl Config {
         if other.no_cache.is_some() {
             self.no_cache = other.no_cache;
         }
+        if other.session_format.is_some() {
+            self.session_format = other.session_format;
+        }
+        if other.case_sensitive_scores.is_some() {
+            self.case_sensitive_scores = other.case_sensitive_scores;
+        }
     }
 }
 
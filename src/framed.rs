@@ -0,0 +1,117 @@
+//! Framed path/content protocol for `om cat --stdin-content`.
+//!
+//! Input is a sequence of entries, each made of two length-prefixed fields:
+//!
+//! ```text
+//! <path length>\n<path bytes><content length>\n<content bytes>
+//! ```
+//!
+//! Lengths are byte counts written as ASCII decimal and terminated by a
+//! single `\n`. Field bytes follow immediately with no separator, so content
+//! may contain anything, including newlines and NUL bytes. Paths must be
+//! UTF-8 and repository-relative (e.g. `src/main.rs`). Input ends at EOF
+//! on an entry boundary.
+
+use std::io::{BufRead, Read};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub path: String,
+    pub content: Vec<u8>,
+}
+
+pub fn read_entries<R: BufRead>(mut reader: R) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+    let mut entries = Vec::new();
+
+    while let Some(path_len) = read_length(&mut reader)? {
+        let path = read_field(&mut reader, path_len, "path")?;
+        let path = String::from_utf8(path).map_err(|_| "Invalid stdin frame: path is not UTF-8")?;
+
+        let content_len = read_length(&mut reader)?
+            .ok_or_else(|| format!("Invalid stdin frame: missing content length for {}", path))?;
+        let content = read_field(&mut reader, content_len, "content")?;
+
+        entries.push(Entry { path, content });
+    }
+
+    Ok(entries)
+}
+
+/// Read a `<decimal>\n` length header. Returns `None` at a clean EOF.
+fn read_length<R: BufRead>(reader: &mut R) -> Result<Option<usize>, Box<dyn std::error::Error>> {
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+
+    if line.pop() != Some(b'\n') {
+        return Err("Invalid stdin frame: unterminated length".into());
+    }
+
+    let text = std::str::from_utf8(&line).unwrap_or("");
+    text.parse::<usize>()
+        .map(Some)
+        .map_err(|_| format!("Invalid stdin frame: expected length, got {:?}", text).into())
+}
+
+/// Read exactly `len` bytes. The buffer grows with the data actually
+/// received, so a bogus length cannot force a huge allocation up front.
+fn read_field<R: BufRead>(
+    reader: &mut R,
+    len: usize,
+    name: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut buf = Vec::new();
+    reader.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() < len {
+        return Err(format!(
+            "Invalid stdin frame: truncated {} ({} bytes expected)",
+            name, len
+        )
+        .into());
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_entries() {
+        let input = b"11\nsrc/main.rs13\nfn main() {}\n9\nREADME.md0\n";
+        let entries = read_entries(&input[..]).unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                Entry {
+                    path: "src/main.rs".to_string(),
+                    content: b"fn main() {}\n".to_vec(),
+                },
+                Entry {
+                    path: "README.md".to_string(),
+                    content: Vec::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert!(read_entries(&b""[..]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        assert!(read_entries(&b"abc\n"[..]).is_err());
+        assert!(read_entries(&b"20\nshort"[..]).is_err());
+        assert!(read_entries(&b"4\na.rs"[..]).is_err());
+        assert!(read_entries(&b"4\na.rs10\nabc"[..]).is_err());
+    }
+
+    #[test]
+    fn test_huge_length_is_truncation_error() {
+        let err = read_entries(&b"4\na.rs18446744073709551615\nabc"[..]).unwrap_err();
+        assert!(err.to_string().contains("truncated content"));
+    }
+}
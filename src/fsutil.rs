@@ -0,0 +1,27 @@
+use std::fs;
+use std::path::Path;
+
+/// Zero-byte regular files. Missing or unreadable paths are not empty.
+pub fn is_empty_file(path: &Path) -> bool {
+    fs::metadata(path).map(|m| m.len() == 0).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_is_empty_file() {
+        let dir = tempdir().unwrap();
+        let empty = dir.path().join("empty.rs");
+        fs::File::create(&empty).unwrap();
+        assert!(is_empty_file(&empty));
+
+        let full = dir.path().join("full.rs");
+        fs::write(&full, "x").unwrap();
+        assert!(!is_empty_file(&full));
+
+        assert!(!is_empty_file(&dir.path().join("missing.rs")));
+    }
+}
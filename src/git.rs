@@ -1,6 +1,10 @@
-use std::collections::HashSet;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
 
 #[derive(Debug)]
 pub enum GitError {
@@ -101,7 +105,94 @@ pub fn ls_files(root: &Path) -> Result<Vec<PathBuf>, GitError> {
     Ok(files)
 }
 
+/// Files that differ between `since` and the working tree: changes
+/// committed after `since` as well as staged and unstaged edits.
+pub fn changed_since(root: &Path, since: &str) -> Result<HashSet<String>, GitError> {
+    let commit = resolve_commit(root, since)?;
+
+    let output = Command::new("git")
+        .arg("diff")
+        .arg("--name-only")
+        .arg("--no-renames")
+        .arg(&commit)
+        .arg("--")
+        .current_dir(root)
+        .output()
+        .map_err(|_| GitError::NotInstalled)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            return Err(GitError::NotARepo);
+        }
+        return Err(GitError::CommandFailed(stderr.to_string()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().map(|line| line.trim().to_string()).collect())
+}
+
+/// Resolve a user-supplied revision to a commit id. The revision is never
+/// parsed as an option, and unknown revisions are an error.
+fn resolve_commit(root: &Path, rev: &str) -> Result<String, GitError> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--verify")
+        .arg("--quiet")
+        .arg("--end-of-options")
+        .arg(format!("{}^{{commit}}", rev))
+        .current_dir(root)
+        .output()
+        .map_err(|_| GitError::NotInstalled)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            return Err(GitError::NotARepo);
+        }
+        return Err(GitError::CommandFailed(format!(
+            "unknown revision '{}'",
+            rev
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Resolved repository roots, keyed by canonicalized input path, so one
+/// process spawns `git rev-parse` at most once per directory.
+#[derive(Default)]
+struct RootCache {
+    roots: Mutex<HashMap<PathBuf, PathBuf>>,
+}
+
+impl RootCache {
+    fn get_or_resolve<F>(&self, path: &Path, resolve: F) -> Result<PathBuf, GitError>
+    where
+        F: FnOnce(&Path) -> Result<PathBuf, GitError>,
+    {
+        let key = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+        if let Some(root) = self.roots.lock().unwrap().get(&key) {
+            return Ok(root.clone());
+        }
+
+        // Errors are not cached; they end the command anyway.
+        let root = resolve(path)?;
+        self.roots.lock().unwrap().insert(key, root.clone());
+        Ok(root)
+    }
+}
+
+lazy_static! {
+    static ref ROOT_CACHE: RootCache = RootCache::default();
+}
+
 pub fn repo_root(path: &Path) -> Result<PathBuf, GitError> {
+    ROOT_CACHE.get_or_resolve(path, resolve_repo_root)
+}
+
+fn resolve_repo_root(path: &Path) -> Result<PathBuf, GitError> {
     let output = Command::new("git")
         .arg("rev-parse")
         .arg("--show-toplevel")
@@ -123,6 +214,215 @@ pub fn repo_root(path: &Path) -> Result<PathBuf, GitError> {
     Ok(root)
 }
 
+/// Resolve a git-lfs pointer to the real object content via `git lfs smudge`.
+pub fn lfs_smudge(root: &Path, pointer: &[u8]) -> Result<Vec<u8>, GitError> {
+    let mut child = Command::new("git")
+        .arg("lfs")
+        .arg("smudge")
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|_| GitError::NotInstalled)?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(pointer)
+            .map_err(|e| GitError::CommandFailed(e.to_string()))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| GitError::CommandFailed(e.to_string()))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GitError::CommandFailed(stderr.to_string()));
+    }
+
+    Ok(output.stdout)
+}
+
+/// One `@@` hunk of a unified diff: the header ranges plus the hunk body
+/// (context, `-` and `+` lines, without trailing newlines).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub header: String,
+    pub lines: Vec<String>,
+}
+
+impl std::fmt::Display for DiffHunk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.header)?;
+        for line in &self.lines {
+            write!(f, "\n{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Working-tree changes relative to HEAD (staged and unstaged) for each of
+/// `files`, as raw unified diffs keyed by path, from a single `git diff`
+/// with `context` lines of context. Files without changes, including
+/// untracked ones, are absent. `None` before the first commit, when there
+/// is no HEAD to diff against. Diffs stay bytes so callers can decode them
+/// like file content.
+pub fn diffs_by_file(
+    root: &Path,
+    files: &[&str],
+    context: usize,
+) -> Result<Option<HashMap<String, Vec<u8>>>, GitError> {
+    if !has_head(root)? {
+        return Ok(None);
+    }
+    if files.is_empty() {
+        return Ok(Some(HashMap::new()));
+    }
+
+    let output = Command::new("git")
+        .arg("-c")
+        .arg("core.quotePath=false")
+        .arg("diff")
+        .arg(format!("-U{}", context))
+        .arg("--no-color")
+        .arg("--no-ext-diff")
+        .arg("--no-renames")
+        .arg("HEAD")
+        .arg("--")
+        .args(files.iter().map(|f| format!(":(literal){}", f)))
+        .current_dir(root)
+        .output()
+        .map_err(|_| GitError::NotInstalled)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            return Err(GitError::NotARepo);
+        }
+        return Err(GitError::CommandFailed(stderr.to_string()));
+    }
+
+    Ok(Some(split_diff(&output.stdout)))
+}
+
+/// Files not tracked by git and not ignored.
+pub fn untracked_files(root: &Path) -> Result<HashSet<String>, GitError> {
+    let output = Command::new("git")
+        .arg("ls-files")
+        .arg("--others")
+        .arg("--exclude-standard")
+        .current_dir(root)
+        .output()
+        .map_err(|_| GitError::NotInstalled)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("not a git repository") {
+            return Err(GitError::NotARepo);
+        }
+        return Err(GitError::CommandFailed(stderr.to_string()));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().map(|line| line.trim().to_string()).collect())
+}
+
+/// Whether HEAD resolves to a commit; false in a repository with no commits.
+fn has_head(root: &Path) -> Result<bool, GitError> {
+    let output = Command::new("git")
+        .arg("rev-parse")
+        .arg("--verify")
+        .arg("--quiet")
+        .arg("HEAD^{commit}")
+        .current_dir(root)
+        .output()
+        .map_err(|_| GitError::NotInstalled)?;
+
+    Ok(output.status.success())
+}
+
+/// Split multi-file `git diff` output into per-file sections, keyed by the
+/// path on the `+++` header line (or `---` for deletions).
+fn split_diff(diff: &[u8]) -> HashMap<String, Vec<u8>> {
+    let mut sections = HashMap::new();
+    let mut path: Option<String> = None;
+    let mut body = Vec::new();
+    let mut in_header = false;
+
+    for line in diff.split_inclusive(|&b| b == b'\n') {
+        if line.starts_with(b"diff --git ") {
+            if let Some(path) = path.take() {
+                sections.insert(path, std::mem::take(&mut body));
+            }
+            body.clear();
+            in_header = true;
+        } else if line.starts_with(b"@@") {
+            in_header = false;
+        } else if in_header {
+            // Git appends a tab to names containing spaces.
+            let text = String::from_utf8_lossy(line);
+            let text = text.trim_end_matches(['\n', '\r', '\t']);
+            if let Some(name) = text.strip_prefix("+++ b/") {
+                path = Some(name.to_string());
+            } else if let Some(name) = text.strip_prefix("--- a/") {
+                path = Some(name.to_string());
+            }
+        }
+        body.extend_from_slice(line);
+    }
+    if let Some(path) = path {
+        sections.insert(path, body);
+    }
+
+    sections
+}
+
+pub fn parse_hunks(diff: &str) -> Vec<DiffHunk> {
+    let mut hunks: Vec<DiffHunk> = Vec::new();
+
+    for line in diff.lines() {
+        if let Some(hunk) = parse_hunk_header(line) {
+            hunks.push(hunk);
+        } else if let Some(hunk) = hunks.last_mut() {
+            if line.starts_with([' ', '+', '-', '\\']) {
+                hunk.lines.push(line.to_string());
+            }
+        }
+    }
+
+    hunks
+}
+
+/// Parse `@@ -a[,b] +c[,d] @@ ...`; omitted counts default to 1.
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = parse_range(old)?;
+    let (new_start, new_lines) = parse_range(new)?;
+
+    Some(DiffHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        header: line.to_string(),
+        lines: Vec::new(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,6 +435,41 @@ mod tests {
         assert!(root.is_ok());
     }
 
+    #[test]
+    fn test_repo_root_cached() {
+        let cwd = env::current_dir().unwrap();
+        assert_eq!(repo_root(&cwd).unwrap(), repo_root(&cwd).unwrap());
+
+        let cache = RootCache::default();
+        let calls = std::cell::Cell::new(0);
+        let spy = |path: &Path| {
+            calls.set(calls.get() + 1);
+            resolve_repo_root(path)
+        };
+
+        let first = cache.get_or_resolve(&cwd, spy).unwrap();
+        let second = cache.get_or_resolve(&cwd, spy).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(calls.get(), 1);
+
+        // A different input path is resolved separately.
+        cache.get_or_resolve(&cwd.join("src"), spy).unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_resolve_commit_rejects_options() {
+        let cwd = env::current_dir().unwrap();
+        assert!(resolve_commit(&cwd, "HEAD").is_ok());
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("out");
+        let rev = format!("--output={}", target.display());
+        assert!(resolve_commit(&cwd, &rev).is_err());
+        assert!(changed_since(&cwd, &rev).is_err());
+        assert!(!target.exists());
+    }
+
     #[test]
     fn test_ls_files() {
         let cwd = env::current_dir().unwrap();
@@ -148,4 +483,68 @@ mod tests {
         let status = git_status(&cwd);
         assert!(status.is_ok());
     }
+
+    #[test]
+    fn test_split_diff() {
+        let diff = [
+            "diff --git a/a.rs b/a.rs",
+            "--- a/a.rs",
+            "+++ b/a.rs",
+            "@@ -1 +1 @@",
+            "-old",
+            "++++ b/not-a-header",
+            "diff --git a/my file.rs b/my file.rs",
+            "deleted file mode 100644",
+            "--- a/my file.rs\t",
+            "+++ /dev/null",
+            "@@ -1 +0,0 @@",
+            "-gone",
+            "",
+        ]
+        .join("\n");
+
+        let sections = split_diff(diff.as_bytes());
+        assert_eq!(sections.len(), 2);
+        let a = String::from_utf8(sections["a.rs"].clone()).unwrap();
+        assert_eq!(
+            parse_hunks(&a)[0].lines,
+            vec!["-old", "++++ b/not-a-header"]
+        );
+        let deleted = String::from_utf8(sections["my file.rs"].clone()).unwrap();
+        assert_eq!(parse_hunks(&deleted)[0].lines, vec!["-gone"]);
+    }
+
+    #[test]
+    fn test_parse_hunks() {
+        let diff = [
+            "diff --git a/f.rs b/f.rs",
+            "index 1111111..2222222 100644",
+            "--- a/f.rs",
+            "+++ b/f.rs",
+            "@@ -1,3 +1,3 @@ fn main() {",
+            " a",
+            "-b",
+            "+B",
+            " c",
+            "@@ -10 +10,2 @@",
+            " x",
+            "+y",
+        ]
+        .join("\n");
+
+        let hunks = parse_hunks(&diff);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!((hunks[0].old_start, hunks[0].old_lines), (1, 3));
+        assert_eq!(hunks[0].lines, vec![" a", "-b", "+B", " c"]);
+        assert_eq!(
+            (
+                hunks[1].old_start,
+                hunks[1].old_lines,
+                hunks[1].new_start,
+                hunks[1].new_lines
+            ),
+            (10, 1, 10, 2)
+        );
+        assert_eq!(hunks[1].to_string(), "@@ -10 +10,2 @@\n x\n+y");
+    }
 }
@@ -0,0 +1,164 @@
+use crate::cli::InitArgs;
+use crate::git;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Starter `.omignore`: common generated, vendored and editor files.
+const OMIGNORE_TEMPLATE: &str = include_str!("../.omignore.example");
+
+/// Starter `.om.toml` listing every option with its default, commented out.
+const CONFIG_TEMPLATE: &str = r#"# om configuration
+#
+# Project settings live in .om.toml at the repository root and override
+# global defaults in ~/.om/config.toml. Command-line flags override both.
+# Every option below is commented out and shows its default; uncomment a
+# line to change it.
+
+# --- Scoring ---------------------------------------------------------------
+
+# Match special filenames (README, Makefile, Dockerfile, main.*, ...)
+# exactly instead of ignoring ASCII case.
+# case_sensitive_scores = false
+
+# --- om tree ---------------------------------------------------------------
+
+# Hide files scoring below this (1-10).
+# min_score = 1
+
+# Maximum directory depth to show (unset: unlimited).
+# depth = 3
+
+# Print a flat list sorted by score instead of a tree.
+# flat = false
+
+# Disable colored output.
+# no_color = false
+
+# Show the whole repository rather than only the current directory.
+# git_root = false
+
+# --- om cat ----------------------------------------------------------------
+
+# Minimum score of files to output (1-10).
+# level = 5
+
+# Omit the project header and trailing summary lines.
+# no_headers = false
+
+# Ignore session history and output every file.
+# no_cache = false
+
+# --- Sessions --------------------------------------------------------------
+
+# File format for new sessions: "json" or "msgpack".
+# session_format = "json"
+"#;
+
+pub fn run(args: InitArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let (path, template) = if args.scoring {
+        (config_path(args.global)?, CONFIG_TEMPLATE)
+    } else {
+        (ignore_path(args.global)?, OMIGNORE_TEMPLATE)
+    };
+
+    write_template(&path, template, args.force)?;
+    println!("Created {}", path.display());
+
+    Ok(())
+}
+
+/// `~/.om/config.toml`, or `.om.toml` at the repository root (the current
+/// directory outside a repository), matching where `load_config` looks.
+fn config_path(global: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if global {
+        let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+        return Ok(home.join(".om").join("config.toml"));
+    }
+    Ok(project_dir()?.join(".om.toml"))
+}
+
+/// `~/.omignore`, or `.omignore` at the repository root.
+fn ignore_path(global: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if global {
+        let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+        return Ok(home.join(".omignore"));
+    }
+    Ok(project_dir()?.join(".omignore"))
+}
+
+fn project_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let cwd = std::env::current_dir()?;
+    Ok(git::repo_root(&cwd).unwrap_or(cwd))
+}
+
+/// Write `template` to `path`, refusing to replace an existing file
+/// unless `force` is set.
+fn write_template(path: &Path, template: &str, force: bool) -> Result<(), String> {
+    if path.exists() && !force {
+        return Err(format!(
+            "{} already exists (use --force to overwrite)",
+            path.display()
+        ));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, template).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_config_template_parses() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".om").join("config.toml");
+        write_template(&path, CONFIG_TEMPLATE, false).unwrap();
+
+        let written = fs::read_to_string(&path).unwrap();
+        let config: Config = toml::from_str(&written).unwrap();
+        assert!(config.min_score.is_none());
+
+        // Every documented option is a real key with a valid value.
+        let uncommented: String = written
+            .lines()
+            .filter_map(|line| line.strip_prefix("# "))
+            .filter(|line| line.contains(" = "))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let table: toml::Table = toml::from_str(&uncommented).unwrap();
+        let config: Config = toml::from_str(&uncommented).unwrap();
+        // One entry per `Config` field.
+        assert_eq!(table.len(), 10);
+        assert_eq!(config.level, Some(5));
+        assert_eq!(config.case_sensitive_scores, Some(false));
+        assert_eq!(config.session_format.as_deref(), Some("json"));
+        assert_eq!(
+            toml::Value::try_from(&config)
+                .unwrap()
+                .as_table()
+                .unwrap()
+                .len(),
+            table.len()
+        );
+    }
+
+    #[test]
+    fn test_write_template_force() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(".om.toml");
+        fs::write(&path, "level = 9\n").unwrap();
+
+        let err = write_template(&path, CONFIG_TEMPLATE, false).unwrap_err();
+        assert!(err.contains("already exists"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "level = 9\n");
+
+        write_template(&path, CONFIG_TEMPLATE, true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), CONFIG_TEMPLATE);
+    }
+}
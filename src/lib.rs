@@ -7,7 +7,7 @@ pub mod session;
 pub use git::{ls_files, repo_root};
 pub use ignore::IgnorePatterns;
 pub use output::{CatOutput, FileOutput, OutputFormat, TreeOutput};
-pub use scorer::{score_file, score_files, ScoredFile};
+pub use scorer::{score_file, score_files, ScoredFile, Scorer};
 pub use session::Session;
 
 use std::path::Path;
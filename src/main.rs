@@ -1,9 +1,14 @@
+mod budget;
 mod cat;
 mod cli;
 mod config;
+mod framed;
+mod fsutil;
 mod git;
 mod ignore;
+mod init;
 mod output;
+mod redact;
 mod scorer;
 mod session;
 mod session_cmd;
@@ -45,12 +50,10 @@ fn main() {
                     args.git_root = git_root;
                 }
             }
-
-            if args.jobs > 0 {
-                rayon::ThreadPoolBuilder::new()
-                    .num_threads(args.jobs)
-                    .build_global()
-                    .ok();
+            if !args.case_sensitive_scores {
+                if let Some(case_sensitive) = config.case_sensitive_scores {
+                    args.case_sensitive_scores = case_sensitive;
+                }
             }
 
             tree::run(args)
@@ -69,9 +72,19 @@ fn main() {
                     args.no_cache = no_cache;
                 }
             }
+            if !args.case_sensitive_scores {
+                if let Some(case_sensitive) = config.case_sensitive_scores {
+                    args.case_sensitive_scores = case_sensitive;
+                }
+            }
+            args.session_format = config.session_format.clone();
             cat::run(args)
         }
-        Commands::Session(args) => session_cmd::run(args),
+        Commands::Session(mut args) => {
+            args.session_format = config.session_format.clone();
+            session_cmd::run(args)
+        }
+        Commands::Init(args) => init::run(args),
     };
 
     if let Err(e) = result {
@@ -1,5 +1,8 @@
-use super::{CatOutput, TreeOutput};
+use super::{CatOutput, RunManifest, ScoreHistogram, TreeOutput};
 use std::error::Error;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
 
 pub fn output_tree(data: &TreeOutput) -> Result<(), Box<dyn Error>> {
     let json = serde_json::to_string_pretty(data)?;
@@ -12,3 +15,32 @@ pub fn output_cat(data: &CatOutput) -> Result<(), Box<dyn Error>> {
     println!("{}", json);
     Ok(())
 }
+
+pub fn output_histogram(data: &ScoreHistogram) -> Result<(), Box<dyn Error>> {
+    let json = serde_json::to_string_pretty(data)?;
+    println!("{}", json);
+    Ok(())
+}
+
+pub fn output_paths<'a>(paths: impl Iterator<Item = &'a str>) -> Result<(), Box<dyn Error>> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for path in paths {
+        writeln!(out, "{}", serde_json::to_string(path)?)?;
+    }
+    Ok(())
+}
+
+/// Append `manifest` as one NDJSON line to `path`, creating the file.
+pub fn append_manifest(path: &Path, manifest: &RunManifest) -> Result<(), Box<dyn Error>> {
+    let mut line = serde_json::to_string(manifest)?;
+    line.push('\n');
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
@@ -1,14 +1,20 @@
 pub mod json;
+pub mod template;
 pub mod xml;
 
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputFormat {
     Text,
     Json,
     Xml,
+    /// One JSON string per line containing just the path (tree only).
+    JsonlPaths,
 }
 
 impl FromStr for OutputFormat {
@@ -19,11 +25,38 @@ impl FromStr for OutputFormat {
             "text" => Ok(OutputFormat::Text),
             "json" => Ok(OutputFormat::Json),
             "xml" => Ok(OutputFormat::Xml),
-            _ => Err(format!("Invalid format: {}. Use text, json, or xml", s)),
+            "jsonl-paths" => Ok(OutputFormat::JsonlPaths),
+            _ => Err(format!(
+                "Invalid format: {}. Use text, json, xml, or jsonl-paths",
+                s
+            )),
         }
     }
 }
 
+/// The project name shown in output: `custom` if given, else the name of
+/// the repository root directory.
+pub fn project_name(root: &Path, custom: Option<&str>) -> String {
+    custom
+        .or_else(|| root.file_name().and_then(|s| s.to_str()))
+        .unwrap_or("project")
+        .to_string()
+}
+
+/// Explain on stderr why a command selected no files. `total` is the number
+/// of files git listed before any filtering.
+pub fn report_no_files(total: usize) {
+    if total == 0 {
+        eprintln!("no files found (empty repo)");
+        eprintln!(
+            "  hint: add files to the repository; untracked files are included unless gitignored"
+        );
+    } else {
+        eprintln!("no files found (all {} files filtered out)", total);
+        eprintln!("  hint: lower --min-score/--level, drop --dirty/--staged/--unstaged, use --git-root, or check .omignore");
+    }
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub struct FileOutput {
     pub path: String,
@@ -33,6 +66,60 @@ pub struct FileOutput {
     pub lines: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    /// Fuzzy match quality for `tree --find`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_score: Option<i64>,
+    /// Section under `--group-tests`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<FileGroup>,
+}
+
+/// Source or test section of `--group-tests` output. Source files come first.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileGroup {
+    Source,
+    Test,
+}
+
+impl FileGroup {
+    pub fn new(is_test: bool) -> Self {
+        if is_test {
+            FileGroup::Test
+        } else {
+            FileGroup::Source
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileGroup::Source => "source",
+            FileGroup::Test => "test",
+        }
+    }
+}
+
+/// A `--json-stream-to` file entry: what a run emitted, without content.
+/// `lines` is only known for `cat`, which reads the files.
+#[derive(Serialize, Debug, Clone)]
+pub struct ManifestFile {
+    pub path: String,
+    pub score: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_score: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<FileGroup>,
+}
+
+/// A file that failed to read for reasons other than being binary.
+#[derive(Serialize, Debug, Clone)]
+pub struct FileError {
+    pub path: String,
+    pub error: String,
 }
 
 #[derive(Serialize, Debug)]
@@ -41,6 +128,26 @@ pub struct TreeOutput {
     pub files: Vec<FileOutput>,
 }
 
+/// Which selection limit stopped `cat` from emitting more files.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BindingLimit {
+    Top,
+    Budget,
+}
+
+impl std::fmt::Display for BindingLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingLimit::Top => write!(f, "top"),
+            BindingLimit::Budget => write!(f, "budget"),
+        }
+    }
+}
+
+/// File counts keyed by score (1-10).
+pub type ScoreHistogram = BTreeMap<i32, usize>;
+
 #[derive(Serialize, Debug)]
 pub struct CatOutput {
     pub project: String,
@@ -49,6 +156,50 @@ pub struct CatOutput {
     pub files_shown: usize,
     pub skipped_binary: usize,
     pub skipped_session: usize,
+    pub skipped_lfs: usize,
+    pub skipped_empty: usize,
+    /// Files skipped for invalid UTF-8 under `--encoding utf8`.
+    pub skipped_encoding: usize,
+    pub skipped_budget: usize,
+    /// `.ombudget` prefixes whose cap caused files to be skipped.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub budget_exceeded: Vec<String>,
     pub total_lines: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binding_limit: Option<BindingLimit>,
+    pub errors: Vec<FileError>,
     pub files: Vec<FileOutput>,
 }
+
+/// One `--json-stream-to` line: when a run happened, its key parameters,
+/// and which files it emitted (without content).
+#[derive(Serialize, Debug)]
+pub struct RunManifest {
+    pub timestamp: u64,
+    pub command: String,
+    pub project: String,
+    pub params: serde_json::Value,
+    pub files: Vec<ManifestFile>,
+}
+
+impl RunManifest {
+    pub fn new(
+        command: &str,
+        project: String,
+        params: serde_json::Value,
+        files: Vec<ManifestFile>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        RunManifest {
+            timestamp,
+            command: command.to_string(),
+            project,
+            params,
+            files,
+        }
+    }
+}
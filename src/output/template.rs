@@ -0,0 +1,116 @@
+use super::CatOutput;
+use serde_json::Value;
+use std::error::Error;
+use tinytemplate::TinyTemplate;
+
+const TEMPLATE_NAME: &str = "cat";
+
+/// Render `data` through a user-supplied TinyTemplate. Optional fields that
+/// JSON output omits (`session`, `binding_limit`, per-file `tokens`, `group`,
+/// ...) are present as null so templates can test them with `{{ if ... }}`;
+/// an omitted `budget_exceeded` is an empty list, so it can also be looped.
+pub fn render_cat(template: &str, data: &CatOutput) -> Result<String, Box<dyn Error>> {
+    let mut context = serde_json::to_value(data)?;
+    fill_nulls(&mut context, &["session", "binding_limit"]);
+    if let Some(object) = context.as_object_mut() {
+        object
+            .entry("budget_exceeded")
+            .or_insert_with(|| Value::Array(Vec::new()));
+    }
+    if let Some(files) = context.get_mut("files").and_then(Value::as_array_mut) {
+        for file in files {
+            fill_nulls(file, &["tokens", "content", "match_score", "group"]);
+        }
+    }
+
+    let mut tt = TinyTemplate::new();
+    tt.set_default_formatter(&tinytemplate::format_unescaped);
+    tt.add_template(TEMPLATE_NAME, template)
+        .map_err(|e| format!("Invalid template: {}", e))?;
+
+    let rendered = tt
+        .render(TEMPLATE_NAME, &context)
+        .map_err(|e| format!("Template rendering failed: {}", e))?;
+    Ok(rendered)
+}
+
+pub fn output_cat(template: &str, data: &CatOutput) -> Result<(), Box<dyn Error>> {
+    print!("{}", render_cat(template, data)?);
+    Ok(())
+}
+
+fn fill_nulls(value: &mut Value, keys: &[&str]) {
+    if let Some(object) = value.as_object_mut() {
+        for key in keys {
+            object.entry(*key).or_insert(Value::Null);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{FileGroup, FileOutput};
+
+    fn sample() -> CatOutput {
+        CatOutput {
+            project: "demo".to_string(),
+            session: None,
+            files_shown: 1,
+            skipped_binary: 0,
+            skipped_session: 0,
+            skipped_lfs: 0,
+            skipped_empty: 0,
+            skipped_encoding: 0,
+            skipped_budget: 0,
+            budget_exceeded: Vec::new(),
+            total_lines: 1,
+            binding_limit: None,
+            errors: Vec::new(),
+            files: vec![FileOutput {
+                path: "src/main.rs".to_string(),
+                score: 10,
+                tokens: None,
+                lines: 1,
+                content: Some("fn main() { a < b }".to_string()),
+                match_score: None,
+                group: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_render_cat() {
+        let template = "{project}{{ if session }} ({session}){{ endif }}\n\
+            {{ for file in files }}<{file.path} score={file.score}>\n{file.content}\n{{ endfor }}";
+        let rendered = render_cat(template, &sample()).unwrap();
+        assert_eq!(
+            rendered,
+            "demo\n<src/main.rs score=10>\nfn main() { a < b }\n"
+        );
+    }
+
+    #[test]
+    fn test_render_cat_omitted_fields() {
+        let template = "{{ if budget_exceeded }}over{{ endif }}\
+            {{ for prefix in budget_exceeded }}{prefix}{{ endfor }}\
+            {{ for file in files }}{{ if file.group }}[{file.group}]{{ endif }}\
+            {{ if file.match_score }}~{{ endif }}{file.path}{{ endfor }}";
+        let mut data = sample();
+        assert_eq!(render_cat(template, &data).unwrap(), "src/main.rs");
+
+        data.budget_exceeded = vec!["vendor/".to_string()];
+        data.files[0].group = Some(FileGroup::Source);
+        data.files[0].match_score = Some(42);
+        assert_eq!(
+            render_cat(template, &data).unwrap(),
+            "overvendor/[source]~src/main.rs"
+        );
+    }
+
+    #[test]
+    fn test_render_cat_invalid_template() {
+        let err = render_cat("{{ for file in files }}", &sample()).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid template"));
+    }
+}
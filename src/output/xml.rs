@@ -1,4 +1,4 @@
-use super::{CatOutput, FileOutput, TreeOutput};
+use super::{CatOutput, FileOutput, ScoreHistogram, TreeOutput};
 use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::Writer;
 use std::error::Error;
@@ -54,8 +54,48 @@ pub fn output_cat(data: &CatOutput) -> Result<(), Box<dyn Error>> {
         "skipped_session",
         &data.skipped_session.to_string(),
     )?;
+    write_element(&mut writer, "skipped_lfs", &data.skipped_lfs.to_string())?;
+    write_element(
+        &mut writer,
+        "skipped_empty",
+        &data.skipped_empty.to_string(),
+    )?;
+    write_element(
+        &mut writer,
+        "skipped_encoding",
+        &data.skipped_encoding.to_string(),
+    )?;
+    write_element(
+        &mut writer,
+        "skipped_budget",
+        &data.skipped_budget.to_string(),
+    )?;
     write_element(&mut writer, "total_lines", &data.total_lines.to_string())?;
 
+    if !data.budget_exceeded.is_empty() {
+        writer.write_event(Event::Start(BytesStart::new("budget_exceeded")))?;
+        for prefix in &data.budget_exceeded {
+            write_element(&mut writer, "prefix", prefix)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("budget_exceeded")))?;
+    }
+
+    if let Some(limit) = data.binding_limit {
+        write_element(&mut writer, "binding_limit", &limit.to_string())?;
+    }
+
+    if !data.errors.is_empty() {
+        writer.write_event(Event::Start(BytesStart::new("errors")))?;
+        for error in &data.errors {
+            let mut elem = BytesStart::new("error");
+            elem.push_attribute(("path", error.path.as_str()));
+            writer.write_event(Event::Start(elem))?;
+            writer.write_event(Event::Text(BytesText::new(&error.error)))?;
+            writer.write_event(Event::End(BytesEnd::new("error")))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("errors")))?;
+    }
+
     let files = BytesStart::new("files");
     writer.write_event(Event::Start(files.borrow()))?;
 
@@ -71,6 +111,28 @@ pub fn output_cat(data: &CatOutput) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+pub fn output_histogram(data: &ScoreHistogram) -> Result<(), Box<dyn Error>> {
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+
+    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+    let histogram = BytesStart::new("histogram");
+    writer.write_event(Event::Start(histogram.borrow()))?;
+
+    for (score, count) in data.iter().rev() {
+        let mut elem = BytesStart::new("score");
+        elem.push_attribute(("value", score.to_string().as_str()));
+        elem.push_attribute(("count", count.to_string().as_str()));
+        writer.write_event(Event::Empty(elem))?;
+    }
+
+    writer.write_event(Event::End(BytesEnd::new("histogram")))?;
+
+    let result = writer.into_inner().into_inner();
+    println!("{}", String::from_utf8(result)?);
+    Ok(())
+}
+
 fn write_file_element<W: std::io::Write>(
     writer: &mut Writer<W>,
     file: &FileOutput,
@@ -84,6 +146,14 @@ fn write_file_element<W: std::io::Write>(
         elem.push_attribute(("tokens", tokens.to_string().as_str()));
     }
 
+    if let Some(match_score) = file.match_score {
+        elem.push_attribute(("match_score", match_score.to_string().as_str()));
+    }
+
+    if let Some(group) = file.group {
+        elem.push_attribute(("group", group.as_str()));
+    }
+
     if let Some(ref content) = file.content {
         writer.write_event(Event::Start(elem.borrow()))?;
 
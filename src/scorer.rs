@@ -1,5 +1,6 @@
 use lazy_static::lazy_static;
 use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -11,249 +12,370 @@ pub struct ScoredFile {
     pub reason: String,
 }
 
+const PROJECT_FILES: &[(&str, i32)] = &[
+    ("Cargo.toml", 8),
+    ("package.json", 8),
+    ("go.mod", 8),
+    ("pom.xml", 8),
+    ("build.gradle", 8),
+    ("Dockerfile", 8),
+    ("docker-compose.yml", 8),
+    ("Makefile", 8),
+    ("CMakeLists.txt", 8),
+    ("tsconfig.json", 8),
+    ("setup.py", 8),
+    ("pyproject.toml", 8),
+];
+
+const IMPORTANT_DIRS: &[&str] = &["src", "core", "lib", "app", "pkg", "internal", "cmd"];
+
+const DOMAIN_DIRS: &[&str] = &[
+    "api",
+    "server",
+    "client",
+    "models",
+    "services",
+    "handlers",
+    "controllers",
+    "routes",
+    "middleware",
+    "database",
+    "db",
+    "auth",
+    "components",
+    "views",
+    "utils",
+];
+
+const TEST_DIRS: &[&str] = &["test", "tests", "spec", "__tests__"];
+
+const LOW_DIRS: &[&str] = &[
+    "vendor",
+    "third_party",
+    "fixtures",
+    "mocks",
+    "docs",
+    "examples",
+    "scripts",
+    "tools",
+    "dist",
+    "build",
+    "out",
+    "target",
+    "node_modules",
+    "archived",
+    "legacy",
+    "debug",
+    "research",
+    "tmp",
+    "temp",
+    "backup",
+    "artifacts",
+    ".artifacts",
+    "drizzle",
+    "migrations",
+];
+
 lazy_static! {
-    static ref PROJECT_FILES: HashMap<&'static str, i32> = {
-        let mut m = HashMap::new();
-        m.insert("Cargo.toml", 8);
-        m.insert("package.json", 8);
-        m.insert("go.mod", 8);
-        m.insert("pom.xml", 8);
-        m.insert("build.gradle", 8);
-        m.insert("Dockerfile", 8);
-        m.insert("docker-compose.yml", 8);
-        m.insert("Makefile", 8);
-        m.insert("CMakeLists.txt", 8);
-        m.insert("tsconfig.json", 8);
-        m.insert("setup.py", 8);
-        m.insert("pyproject.toml", 8);
-        m
-    };
-    static ref IMPORTANT_DIRS: Vec<&'static str> =
-        vec!["src", "core", "lib", "app", "pkg", "internal", "cmd",];
-    static ref DOMAIN_DIRS: Vec<&'static str> = vec![
-        "api",
-        "server",
-        "client",
-        "models",
-        "services",
-        "handlers",
-        "controllers",
-        "routes",
-        "middleware",
-        "database",
-        "db",
-        "auth",
-        "components",
-        "views",
-        "utils",
-    ];
-    static ref TEST_DIRS: Vec<&'static str> = vec!["test", "tests", "spec", "__tests__",];
-    static ref LOW_DIRS: Vec<&'static str> = vec![
-        "vendor",
-        "third_party",
-        "fixtures",
-        "mocks",
-        "docs",
-        "examples",
-        "scripts",
-        "tools",
-        "dist",
-        "build",
-        "out",
-        "target",
-        "node_modules",
-        "archived",
-        "legacy",
-        "debug",
-        "research",
-        "tmp",
-        "temp",
-        "backup",
-        "artifacts",
-        ".artifacts",
-        "drizzle",
-        "migrations",
-    ];
+    static ref DEFAULT_SCORER: Scorer = Scorer::default();
 }
 
-pub fn score_file(filepath: &str) -> ScoredFile {
-    let path = Path::new(filepath);
-    let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
-    let mut score = 7;
-    let mut reasons = Vec::new();
+/// Scoring rules: which filenames are project files and which directory
+/// names raise or lower a file's score. `Scorer::default()` holds the
+/// built-in tables; override fields to customize a single invocation.
+#[derive(Debug, Clone)]
+pub struct Scorer {
+    pub project_files: HashMap<String, i32>,
+    pub important_dirs: Vec<String>,
+    pub domain_dirs: Vec<String>,
+    pub test_dirs: Vec<String>,
+    pub low_dirs: Vec<String>,
+    /// Match special filenames (README, Dockerfile, main.*, ...) ignoring
+    /// ASCII case, since git may report `readme.md` on case-insensitive
+    /// filesystems. Extension checks are always exact.
+    pub case_insensitive_names: bool,
+}
 
-    if filename == "main.rs" || filename == "lib.rs" || filename == "mod.rs" {
-        return ScoredFile {
-            path: filepath.to_string(),
-            score: 10,
-            reason: "entry point".to_string(),
-        };
+impl Default for Scorer {
+    fn default() -> Self {
+        let dirs = |names: &[&str]| names.iter().map(|s| s.to_string()).collect();
+
+        Scorer {
+            project_files: PROJECT_FILES
+                .iter()
+                .map(|&(name, score)| (name.to_string(), score))
+                .collect(),
+            important_dirs: dirs(IMPORTANT_DIRS),
+            domain_dirs: dirs(DOMAIN_DIRS),
+            test_dirs: dirs(TEST_DIRS),
+            low_dirs: dirs(LOW_DIRS),
+            case_insensitive_names: true,
+        }
     }
+}
 
-    if filename.starts_with("main.")
-        || filename.starts_with("index.")
-        || filename.starts_with("app.")
-        || filename.starts_with("server.")
-        || filename.starts_with("cli.")
-    {
-        return ScoredFile {
-            path: filepath.to_string(),
-            score: 10,
-            reason: "entry point".to_string(),
-        };
-    }
+/// Test naming conventions: `test_x.py`, `x_test.go`, `x.test.ts`, `x.spec.js`.
+fn is_test_filename(filename: &str) -> bool {
+    filename.starts_with("test_")
+        || filename.contains("_test.")
+        || filename.contains(".test.")
+        || filename.contains(".spec.")
+}
 
-    if filename == "README.md" || filename == "README" || filename == "README.rst" {
-        let mut score = 10;
-        let components: Vec<&str> = filepath.split('/').collect();
-        for component in components {
-            if LOW_DIRS.contains(&component) || TEST_DIRS.contains(&component) {
-                score = 5;
-                break;
-            }
+/// Whether any directory component of `components` (all but the last,
+/// which is the filename) is in `dirs`.
+fn in_any_dir(components: &[&str], dirs: &[String]) -> bool {
+    components[..components.len().saturating_sub(1)]
+        .iter()
+        .any(|c| dirs.iter().any(|d| d == c))
+}
+
+impl Scorer {
+    fn name_is(&self, filename: &str, name: &str) -> bool {
+        if self.case_insensitive_names {
+            filename.eq_ignore_ascii_case(name)
+        } else {
+            filename == name
         }
-        return ScoredFile {
-            path: filepath.to_string(),
-            score,
-            reason: "readme".to_string(),
-        };
     }
 
-    if filename.starts_with("config.") || filename.starts_with("settings.") {
-        return ScoredFile {
-            path: filepath.to_string(),
-            score: 9,
-            reason: "config".to_string(),
-        };
+    fn name_starts_with(&self, filename: &str, prefix: &str) -> bool {
+        if self.case_insensitive_names {
+            filename
+                .get(..prefix.len())
+                .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+        } else {
+            filename.starts_with(prefix)
+        }
     }
 
-    if filename.ends_with(".lock")
-        || filename.contains("-lock.")
-        || filename.contains(".lock.")
-        || filename.ends_with(".min.js")
-        || filename.ends_with(".min.css")
-        || filename.ends_with(".map")
-        || filename.ends_with(".d.ts")
-        || filename.ends_with(".pyc")
-        || filename.contains(".generated.")
-        || filename.ends_with(".backup")
-        || filename.ends_with(".bak")
-        || filename.ends_with(".tmp")
-        || filename.ends_with(".sql")
-    {
-        return ScoredFile {
-            path: filepath.to_string(),
-            score: 2,
-            reason: "generated or insignificant".to_string(),
-        };
+    fn project_file_score(&self, filename: &str) -> Option<i32> {
+        self.project_files.get(filename).copied().or_else(|| {
+            self.project_files
+                .iter()
+                .find(|(name, _)| self.name_is(filename, name))
+                .map(|(_, &score)| score)
+        })
     }
 
-    if let Some(&project_score) = PROJECT_FILES.get(filename) {
-        return ScoredFile {
-            path: filepath.to_string(),
-            score: project_score,
-            reason: "project file".to_string(),
-        };
-    }
+    pub fn score(&self, filepath: &str) -> ScoredFile {
+        let path = Path::new(filepath);
+        let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+        let mut score = 7;
+        let mut reasons = Vec::new();
+
+        if self.name_is(filename, "main.rs")
+            || self.name_is(filename, "lib.rs")
+            || self.name_is(filename, "mod.rs")
+        {
+            return ScoredFile {
+                path: filepath.to_string(),
+                score: 10,
+                reason: "entry point".to_string(),
+            };
+        }
 
-    if filename.starts_with("test_")
-        || filename.contains("_test.")
-        || filename.contains(".test.")
-        || filename.contains(".spec.")
-    {
-        return ScoredFile {
-            path: filepath.to_string(),
-            score: 5,
-            reason: "test file".to_string(),
-        };
-    }
+        if self.name_starts_with(filename, "main.")
+            || self.name_starts_with(filename, "index.")
+            || self.name_starts_with(filename, "app.")
+            || self.name_starts_with(filename, "server.")
+            || self.name_starts_with(filename, "cli.")
+        {
+            return ScoredFile {
+                path: filepath.to_string(),
+                score: 10,
+                reason: "entry point".to_string(),
+            };
+        }
 
-    if filename == "__init__.py" {
-        return ScoredFile {
-            path: filepath.to_string(),
-            score: 3,
-            reason: "init file".to_string(),
-        };
-    }
+        if self.name_is(filename, "README.md")
+            || self.name_is(filename, "README")
+            || self.name_is(filename, "README.rst")
+        {
+            let mut score = 10;
+            let is_low = |c: &str| self.low_dirs.iter().chain(&self.test_dirs).any(|d| d == c);
+            if filepath.split('/').any(is_low) {
+                score = 5;
+            }
+            return ScoredFile {
+                path: filepath.to_string(),
+                score,
+                reason: "readme".to_string(),
+            };
+        }
+
+        if self.name_starts_with(filename, "config.")
+            || self.name_starts_with(filename, "settings.")
+        {
+            return ScoredFile {
+                path: filepath.to_string(),
+                score: 9,
+                reason: "config".to_string(),
+            };
+        }
+
+        if filename.ends_with(".lock")
+            || filename.contains("-lock.")
+            || filename.contains(".lock.")
+            || filename.ends_with(".min.js")
+            || filename.ends_with(".min.css")
+            || filename.ends_with(".map")
+            || filename.ends_with(".d.ts")
+            || filename.ends_with(".pyc")
+            || filename.contains(".generated.")
+            || filename.ends_with(".backup")
+            || filename.ends_with(".bak")
+            || filename.ends_with(".tmp")
+            || filename.ends_with(".sql")
+            || filename == "go.sum"
+            || filename.ends_with(".snap")
+            || filename.ends_with(".woff")
+            || filename.ends_with(".woff2")
+            || filename.ends_with(".wasm")
+        {
+            return ScoredFile {
+                path: filepath.to_string(),
+                score: 2,
+                reason: "generated or insignificant".to_string(),
+            };
+        }
+
+        if let Some(project_score) = self.project_file_score(filename) {
+            return ScoredFile {
+                path: filepath.to_string(),
+                score: project_score,
+                reason: "project file".to_string(),
+            };
+        }
+
+        if is_test_filename(filename) {
+            return ScoredFile {
+                path: filepath.to_string(),
+                score: 5,
+                reason: "test file".to_string(),
+            };
+        }
+
+        if self.name_is(filename, "__init__.py") {
+            return ScoredFile {
+                path: filepath.to_string(),
+                score: 3,
+                reason: "init file".to_string(),
+            };
+        }
 
-    let components: Vec<&str> = filepath.split('/').collect();
-    let depth = components.len() - 1;
+        let components: Vec<&str> = filepath.split('/').collect();
+        let depth = components.len() - 1;
 
-    for component in &components[..components.len().saturating_sub(1)] {
-        if IMPORTANT_DIRS.contains(component) {
+        if in_any_dir(&components, &self.important_dirs) {
             score += 2;
             reasons.push("important dir");
-            break;
         }
-    }
 
-    for component in &components[..components.len().saturating_sub(1)] {
-        if DOMAIN_DIRS.contains(component) {
+        if in_any_dir(&components, &self.domain_dirs) {
             score += 1;
             reasons.push("domain dir");
-            break;
         }
-    }
 
-    for component in &components[..components.len().saturating_sub(1)] {
-        if TEST_DIRS.contains(component) {
+        if in_any_dir(&components, &self.test_dirs) {
             score -= 2;
             reasons.push("test dir");
-            break;
         }
-    }
 
-    for component in &components[..components.len().saturating_sub(1)] {
-        if LOW_DIRS.contains(component) {
+        if in_any_dir(&components, &self.low_dirs) {
             score -= 3;
             reasons.push("low priority dir");
-            break;
+        }
+
+        if depth == 0 {
+            score += 1;
+            reasons.push("root level");
+        } else if depth > 4 {
+            score -= 2;
+            reasons.push("deep nesting");
+        } else if depth > 2 {
+            score -= 1;
+            reasons.push("nested");
+        }
+
+        let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
+        if extension == "proto"
+            || extension == "graphql"
+            || extension == "gql"
+            || extension == "thrift"
+        {
+            score += 1;
+            reasons.push("schema file");
+        }
+
+        if (extension == "md" || extension == "rst")
+            && filename != "README.md"
+            && filename != "README.rst"
+        {
+            score -= 1;
+            reasons.push("doc file");
+        }
+
+        score = score.clamp(1, 10);
+
+        let reason = if reasons.is_empty() {
+            "base score".to_string()
+        } else {
+            reasons.join(", ")
+        };
+
+        ScoredFile {
+            path: filepath.to_string(),
+            score,
+            reason,
         }
     }
 
-    if depth == 0 {
-        score += 1;
-        reasons.push("root level");
-    } else if depth > 4 {
-        score -= 2;
-        reasons.push("deep nesting");
-    } else if depth > 2 {
-        score -= 1;
-        reasons.push("nested");
+    /// Whether `filepath` is a test file: named like one, or inside a test
+    /// directory. The same heuristics lower test scores in `score`.
+    pub fn is_test(&self, filepath: &str) -> bool {
+        let components: Vec<&str> = filepath.split('/').collect();
+        let filename = components.last().copied().unwrap_or("");
+        is_test_filename(filename) || in_any_dir(&components, &self.test_dirs)
     }
 
-    let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-    if extension == "proto" || extension == "graphql" || extension == "gql" || extension == "thrift"
-    {
-        score += 1;
-        reasons.push("schema file");
+    /// Score `files` in parallel on the current rayon pool.
+    pub fn score_many(&self, files: &[String]) -> Vec<ScoredFile> {
+        self.score_many_with(files, |_| {})
     }
 
-    if (extension == "md" || extension == "rst")
-        && filename != "README.md"
-        && filename != "README.rst"
+    /// `score_many`, calling `observe` with each path on the thread that
+    /// scores it.
+    fn score_many_with<F>(&self, files: &[String], observe: F) -> Vec<ScoredFile>
+    where
+        F: Fn(&str) + Sync,
     {
-        score -= 1;
-        reasons.push("doc file");
+        files
+            .par_iter()
+            .map(|f| {
+                observe(f);
+                self.score(f)
+            })
+            .collect()
     }
+}
 
-    score = score.clamp(1, 10);
-
-    let reason = if reasons.is_empty() {
-        "base score".to_string()
-    } else {
-        reasons.join(", ")
-    };
-
-    ScoredFile {
-        path: filepath.to_string(),
-        score,
-        reason,
-    }
+#[allow(dead_code)]
+pub fn score_file(filepath: &str) -> ScoredFile {
+    DEFAULT_SCORER.score(filepath)
 }
 
+#[allow(dead_code)]
 pub fn score_files(files: Vec<String>) -> Vec<ScoredFile> {
-    files.into_par_iter().map(|f| score_file(&f)).collect()
+    DEFAULT_SCORER.score_many(&files)
+}
+
+/// Build a thread pool limited to `max_concurrency` threads (0 = one per CPU).
+/// Scoped pools keep concurrency per call instead of fixing the global pool.
+pub fn thread_pool(max_concurrency: usize) -> Result<ThreadPool, ThreadPoolBuildError> {
+    ThreadPoolBuilder::new()
+        .num_threads(max_concurrency)
+        .build()
 }
 
 #[cfg(test)]
@@ -312,6 +434,18 @@ mod tests {
         assert_eq!(score_file("foo.test.ts").score, 5);
     }
 
+    #[test]
+    fn test_is_test() {
+        let scorer = Scorer::default();
+        assert!(scorer.is_test("test_foo.py"));
+        assert!(scorer.is_test("src/foo.spec.ts"));
+        assert!(scorer.is_test("tests/integration.rs"));
+        assert!(scorer.is_test("src/__tests__/helpers.js"));
+        assert!(!scorer.is_test("src/main.rs"));
+        assert!(!scorer.is_test("src/testing.rs"));
+        assert!(!scorer.is_test("tests"));
+    }
+
     #[test]
     fn test_generated() {
         assert_eq!(score_file("package-lock.json").score, 2);
@@ -319,6 +453,23 @@ mod tests {
         assert_eq!(score_file("types.generated.ts").score, 2);
     }
 
+    #[test]
+    fn test_lockfiles_and_artifacts() {
+        for path in [
+            "Gemfile.lock",
+            "composer.lock",
+            "go.sum",
+            "flake.lock",
+            "src/__snapshots__/Button.test.tsx.snap",
+            "assets/fonts/inter.woff",
+            "assets/fonts/inter.woff2",
+            "pkg/module_bg.wasm",
+        ] {
+            assert_eq!(score_file(path).score, 2, "{}", path);
+        }
+        assert_eq!(score_file("go.mod").score, 8);
+    }
+
     #[test]
     fn test_directory_modifiers() {
         let scored_src = score_file("src/handler.rs");
@@ -366,4 +517,85 @@ mod tests {
         assert_eq!(score_file("docs.md").score, 7);
         assert_eq!(score_file("README.md").score, 10);
     }
+
+    #[test]
+    fn test_single_thread_pool() {
+        use std::collections::HashSet;
+        use std::sync::Mutex;
+
+        let files: Vec<String> = (0..500).map(|i| format!("src/file{}.rs", i)).collect();
+        let scorer = Scorer::default();
+        let threads = Mutex::new(HashSet::new());
+        // The pool `tree` and `cat` build for `--jobs`, running their
+        // scoring call.
+        let run = |jobs: usize| {
+            let pool = thread_pool(jobs).unwrap();
+            let scored = pool.install(|| {
+                scorer.score_many_with(&files, |_| {
+                    threads.lock().unwrap().insert(std::thread::current().id());
+                })
+            });
+            assert_eq!(scored.len(), 500);
+            pool
+        };
+
+        let pool = run(1);
+        let worker = pool.install(|| std::thread::current().id());
+        assert_eq!(*threads.lock().unwrap(), HashSet::from([worker]));
+
+        threads.lock().unwrap().clear();
+        run(2);
+        assert!(threads.lock().unwrap().len() <= 2);
+    }
+
+    #[test]
+    fn test_custom_dirs() {
+        let scorer = Scorer {
+            important_dirs: vec!["engine".to_string()],
+            low_dirs: vec!["attic".to_string()],
+            ..Scorer::default()
+        };
+
+        assert_eq!(scorer.score("engine/physics.rs").score, 9);
+        assert_eq!(scorer.score("attic/physics.rs").score, 4);
+        assert_eq!(scorer.score("attic/README.md").score, 5);
+        // Built-in dirs no longer apply once replaced.
+        assert_eq!(scorer.score("src/physics.rs").score, 7);
+        assert_eq!(scorer.score("vendor/physics.rs").score, 7);
+        assert_eq!(score_file("vendor/physics.rs").score, 4);
+    }
+
+    #[test]
+    fn test_case_insensitive_names() {
+        assert_eq!(score_file("readme.md").score, 10);
+        assert_eq!(score_file("ops/DOCKERFILE").score, 8);
+        assert_eq!(score_file("ops/makefile").score, 8);
+        assert_eq!(score_file("Main.go").score, 10);
+        // Extension checks stay exact.
+        assert_eq!(score_file("docs.MD").score, 8);
+
+        let exact = Scorer {
+            case_insensitive_names: false,
+            ..Scorer::default()
+        };
+        assert_eq!(exact.score("readme.md").score, 7);
+        assert_eq!(exact.score("ops/DOCKERFILE").score, 7);
+        assert_eq!(exact.score("ops/makefile").score, 7);
+        assert_eq!(exact.score("README.md").score, 10);
+        assert_eq!(exact.score("ops/Dockerfile").score, 8);
+    }
+
+    #[test]
+    fn test_custom_project_files() {
+        let mut scorer = Scorer::default();
+        scorer.project_files.insert("BUILD.bazel".to_string(), 9);
+
+        assert_eq!(scorer.score("BUILD.bazel").score, 9);
+        assert_eq!(score_file("BUILD.bazel").score, 8);
+
+        let files = vec!["BUILD.bazel".to_string(), "src/main.rs".to_string()];
+        let scored = scorer.score_many(&files);
+        assert_eq!(scored[0].score, 9);
+        assert_eq!(scored[1].score, 10);
+    }
 }
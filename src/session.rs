@@ -2,13 +2,70 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// On-disk encoding for session files, identified by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SessionFormat {
+    /// Pretty-printed JSON (`.json`), easy to inspect by hand.
+    #[default]
+    Json,
+    /// MessagePack (`.msgpack`), compact and faster for large sessions.
+    MsgPack,
+}
+
+impl SessionFormat {
+    const ALL: [SessionFormat; 2] = [SessionFormat::Json, SessionFormat::MsgPack];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            SessionFormat::Json => "json",
+            SessionFormat::MsgPack => "msgpack",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension().and_then(|s| s.to_str())?;
+        Self::ALL.into_iter().find(|f| f.extension() == ext)
+    }
+
+    /// Format for newly created sessions: `OM_SESSION_FORMAT`, then the
+    /// configured value, then JSON.
+    pub fn resolve(configured: Option<&str>) -> Result<Self, String> {
+        match std::env::var("OM_SESSION_FORMAT")
+            .ok()
+            .as_deref()
+            .or(configured)
+        {
+            Some(value) => value.parse(),
+            None => Ok(SessionFormat::default()),
+        }
+    }
+}
+
+impl FromStr for SessionFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(SessionFormat::Json),
+            "msgpack" | "messagepack" => Ok(SessionFormat::MsgPack),
+            _ => Err(format!(
+                "Invalid session format: {}. Use json or msgpack",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Session {
     pub name: String,
     pub files: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_root: Option<PathBuf>,
     #[serde(skip)]
     path: PathBuf,
 }
@@ -22,34 +79,56 @@ impl Session {
         format!("sess-{}", timestamp)
     }
 
+    #[allow(dead_code)]
     pub fn load(name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_with_format(name, SessionFormat::default())
+    }
+
+    /// Load a session in whichever format it was saved in. New sessions are
+    /// created in `preferred` format.
+    pub fn load_with_format(
+        name: &str,
+        preferred: SessionFormat,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let sessions_dir = Self::sessions_dir()?;
-        let path = sessions_dir.join(format!("{}.json", name));
-
-        if path.exists() {
-            let content = fs::read_to_string(&path)?;
-            let mut session: Session = serde_json::from_str(&content)?;
-            session.path = path;
-            Ok(session)
-        } else {
-            Ok(Session {
-                name: name.to_string(),
-                files: HashMap::new(),
-                path,
-            })
+
+        for format in SessionFormat::ALL {
+            let path = sessions_dir.join(format!("{}.{}", name, format.extension()));
+            if path.exists() {
+                return Self::read_file(&path, format);
+            }
         }
+
+        Ok(Session {
+            name: name.to_string(),
+            files: HashMap::new(),
+            repo_root: None,
+            path: sessions_dir.join(format!("{}.{}", name, preferred.extension())),
+        })
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let sessions_dir = Self::sessions_dir()?;
-        fs::create_dir_all(&sessions_dir)?;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
 
-        let content = serde_json::to_string_pretty(&self)?;
-        fs::write(&self.path, content)?;
+        match SessionFormat::from_path(&self.path).unwrap_or_default() {
+            SessionFormat::Json => fs::write(&self.path, serde_json::to_string_pretty(&self)?)?,
+            SessionFormat::MsgPack => fs::write(&self.path, rmp_serde::to_vec_named(&self)?)?,
+        }
 
         Ok(())
     }
 
+    fn read_file(path: &Path, format: SessionFormat) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut session: Session = match format {
+            SessionFormat::Json => serde_json::from_str(&fs::read_to_string(path)?)?,
+            SessionFormat::MsgPack => rmp_serde::from_slice(&fs::read(path)?)?,
+        };
+        session.path = path.to_path_buf();
+        Ok(session)
+    }
+
     pub fn was_read(&self, path: &str, hash: &str) -> bool {
         self.files.get(path).map(|h| h == hash).unwrap_or(false)
     }
@@ -66,15 +145,66 @@ impl Session {
 
     pub fn clear(name: &str) -> Result<(), Box<dyn std::error::Error>> {
         let sessions_dir = Self::sessions_dir()?;
-        let path = sessions_dir.join(format!("{}.json", name));
 
-        if path.exists() {
-            fs::remove_file(&path)?;
+        for format in SessionFormat::ALL {
+            let path = sessions_dir.join(format!("{}.{}", name, format.extension()));
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
         }
 
         Ok(())
     }
 
+    pub fn list_all() -> Result<Vec<Session>, Box<dyn std::error::Error>> {
+        Self::list_in(&Self::sessions_dir()?)
+    }
+
+    /// Remove sessions whose recorded repository root no longer exists.
+    /// Sessions without a recorded root are kept. Returns the number removed.
+    pub fn gc() -> Result<usize, Box<dyn std::error::Error>> {
+        Self::remove_stale(Self::list_all()?)
+    }
+
+    fn list_in(dir: &Path) -> Result<Vec<Session>, Box<dyn std::error::Error>> {
+        let mut sessions = Vec::new();
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(sessions),
+            Err(e) => return Err(e.into()),
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            let Some(format) = SessionFormat::from_path(&path) else {
+                continue;
+            };
+
+            if let Ok(session) = Self::read_file(&path, format) {
+                sessions.push(session);
+            }
+        }
+
+        sessions.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(sessions)
+    }
+
+    fn remove_stale(sessions: Vec<Session>) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut removed = 0;
+
+        for session in sessions {
+            if let Some(ref root) = session.repo_root {
+                if !root.exists() {
+                    fs::remove_file(&session.path)?;
+                    removed += 1;
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
     fn sessions_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
         let home = dirs::home_dir().ok_or("Could not determine home directory")?;
         Ok(home.join(".om").join("sessions"))
@@ -97,6 +227,7 @@ mod tests {
         let mut session = Session {
             name: "test".to_string(),
             files: HashMap::new(),
+            repo_root: None,
             path: PathBuf::from("/tmp/test.json"),
         };
 
@@ -112,4 +243,86 @@ mod tests {
         let id = Session::generate_id();
         assert!(id.starts_with("sess-"));
     }
+
+    #[test]
+    fn test_gc_removes_sessions_for_missing_repos() {
+        let sessions_dir = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        let kept_repo = tempfile::tempdir().unwrap();
+
+        let make = |name: &str, root: Option<PathBuf>| Session {
+            name: name.to_string(),
+            files: HashMap::new(),
+            repo_root: root,
+            path: sessions_dir.path().join(format!("{}.json", name)),
+        };
+        let gone = make("gone", Some(repo.path().to_path_buf()));
+        let kept = make("kept", Some(kept_repo.path().to_path_buf()));
+        let legacy = make("legacy", None);
+        for s in [&gone, &kept, &legacy] {
+            fs::write(&s.path, serde_json::to_string(s).unwrap()).unwrap();
+        }
+
+        assert_eq!(Session::list_in(sessions_dir.path()).unwrap().len(), 3);
+
+        drop(repo);
+        assert_eq!(
+            Session::remove_stale(Session::list_in(sessions_dir.path()).unwrap()).unwrap(),
+            1
+        );
+        assert!(!gone.path.exists());
+        assert!(kept.path.exists());
+        assert!(legacy.path.exists());
+
+        assert_eq!(
+            Session::remove_stale(Session::list_in(sessions_dir.path()).unwrap()).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_repo_root_defaults_when_missing() {
+        let session: Session = serde_json::from_str(r#"{"name":"old","files":{}}"#).unwrap();
+        assert!(session.repo_root.is_none());
+    }
+
+    #[test]
+    fn test_msgpack_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut session = Session {
+            name: "packed".to_string(),
+            files: HashMap::new(),
+            repo_root: Some(PathBuf::from("/repo")),
+            path: dir.path().join("packed.msgpack"),
+        };
+        session.mark_read("src/main.rs", "abc123");
+        session.save().unwrap();
+
+        let bytes = fs::read(&session.path).unwrap();
+        assert!(serde_json::from_slice::<serde_json::Value>(&bytes).is_err());
+
+        let loaded = Session::read_file(&session.path, SessionFormat::MsgPack).unwrap();
+        assert_eq!(loaded.name, "packed");
+        assert!(loaded.was_read("src/main.rs", "abc123"));
+        assert_eq!(loaded.repo_root, Some(PathBuf::from("/repo")));
+
+        let listed = Session::list_in(dir.path()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].path, session.path);
+    }
+
+    #[test]
+    fn test_session_format_detection() {
+        assert_eq!(
+            SessionFormat::from_path(Path::new("a.json")),
+            Some(SessionFormat::Json)
+        );
+        assert_eq!(
+            SessionFormat::from_path(Path::new("a.msgpack")),
+            Some(SessionFormat::MsgPack)
+        );
+        assert_eq!(SessionFormat::from_path(Path::new("a.txt")), None);
+        assert_eq!("msgpack".parse(), Ok(SessionFormat::MsgPack));
+        assert!("yaml".parse::<SessionFormat>().is_err());
+    }
 }
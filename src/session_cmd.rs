@@ -1,19 +1,20 @@
 use crate::cli::{SessionArgs, SessionCommand};
-use crate::session::Session;
+use crate::session::{Session, SessionFormat};
 
 pub fn run(args: SessionArgs) -> Result<(), Box<dyn std::error::Error>> {
     match args.command {
-        None => smart_init(),
+        None => smart_init(SessionFormat::resolve(args.session_format.as_deref())?),
         Some(SessionCommand::Clear { name }) => clear(&name),
+        Some(SessionCommand::Gc) => gc(),
     }
 }
 
-fn smart_init() -> Result<(), Box<dyn std::error::Error>> {
+fn smart_init(format: SessionFormat) -> Result<(), Box<dyn std::error::Error>> {
     if let Ok(existing) = std::env::var("OM_SESSION") {
         println!("echo 'Session already active: {}'", existing);
     } else {
         let session_id = Session::generate_id();
-        let session = Session::load(&session_id)?;
+        let session = Session::load_with_format(&session_id, format)?;
         session.save()?;
         println!(
             "export OM_SESSION={}; echo 'Session created: {}'",
@@ -36,3 +37,9 @@ fn clear(name: &str) -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+fn gc() -> Result<(), Box<dyn std::error::Error>> {
+    let removed = Session::gc()?;
+    println!("Removed {} stale session(s)", removed);
+    Ok(())
+}
@@ -1,18 +1,25 @@
 use crate::cli::TreeArgs;
+use crate::fsutil::is_empty_file;
 use crate::git;
 use crate::ignore::IgnorePatterns;
-use crate::output::{self, FileOutput, OutputFormat, TreeOutput};
-use crate::scorer::{score_files, ScoredFile};
+use crate::output::{
+    self, FileGroup, FileOutput, ManifestFile, OutputFormat, RunManifest, ScoreHistogram,
+    TreeOutput,
+};
+use crate::scorer::{self, ScoredFile, Scorer};
 use colored::*;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
 use rayon::prelude::*;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 pub fn run(args: TreeArgs) -> Result<(), Box<dyn std::error::Error>> {
-    let path = PathBuf::from(args.path.unwrap_or_else(|| ".".to_string()));
+    let path = PathBuf::from(args.path.clone().unwrap_or_else(|| ".".to_string()));
     let root = git::repo_root(&path)?;
 
     let files = git::ls_files(&root)?;
+    let total_files = files.len();
     let ignore = IgnorePatterns::load(&root);
 
     let git_status = if args.dirty || args.staged || args.unstaged {
@@ -21,6 +28,12 @@ pub fn run(args: TreeArgs) -> Result<(), Box<dyn std::error::Error>> {
         None
     };
 
+    let changed = args
+        .since
+        .as_deref()
+        .map(|since| git::changed_since(&root, since))
+        .transpose()?;
+
     let filter_prefix = if args.git_root {
         None
     } else {
@@ -62,22 +75,15 @@ pub fn run(args: TreeArgs) -> Result<(), Box<dyn std::error::Error>> {
                 true
             }
         })
+        .filter(|p| changed.as_ref().is_none_or(|changed| changed.contains(p)))
         .collect();
 
-    let jobs = if args.jobs == 0 {
-        num_cpus::get()
-    } else {
-        args.jobs
-    };
-
-    let mut scored: Vec<ScoredFile> = if jobs > 1 {
-        file_strs
-            .par_iter()
-            .map(|f| crate::scorer::score_file(f))
-            .collect()
-    } else {
-        score_files(file_strs)
+    let pool = scorer::thread_pool(args.jobs)?;
+    let scorer = Scorer {
+        case_insensitive_names: !args.case_sensitive_scores,
+        ..Scorer::default()
     };
+    let mut scored = pool.install(|| scorer.score_many(&file_strs));
 
     scored.retain(|f| f.score >= args.min_score.unwrap_or(1));
 
@@ -88,44 +94,166 @@ pub fn run(args: TreeArgs) -> Result<(), Box<dyn std::error::Error>> {
         });
     }
 
+    let match_scores = match args.find {
+        Some(ref query) => {
+            let matches = find_matches(&scored, query);
+            scored.retain(|f| matches.contains_key(&f.path));
+            scored.sort_by(|a, b| {
+                matches[&b.path]
+                    .cmp(&matches[&a.path])
+                    .then(b.score.cmp(&a.score))
+                    .then(a.path.cmp(&b.path))
+            });
+            matches
+        }
+        None => HashMap::new(),
+    };
+
+    // Last, so only files that would otherwise be listed are stat'ed.
+    if !args.include_empty {
+        scored.retain(|f| !is_empty_file(&root.join(&f.path)));
+    }
+
+    // Source files first, then tests, each keeping score (or match) order.
+    let group_of = |path: &str| {
+        args.group_tests
+            .then(|| FileGroup::new(scorer.is_test(path)))
+    };
+    if args.group_tests {
+        if args.find.is_none() {
+            scored.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.cmp(&b.path)));
+        }
+        scored.sort_by_key(|f| scorer.is_test(&f.path));
+    }
+
     let format = if let Some(ref fmt) = args.format {
         fmt.parse::<OutputFormat>()?
     } else {
         OutputFormat::Text
     };
 
+    let dir_scores = args
+        .dir_scores
+        .as_deref()
+        .map(str::parse::<DirScore>)
+        .transpose()?;
+
+    if scored.is_empty() {
+        output::report_no_files(total_files);
+    }
+
+    // Built now, appended once the output has been written.
+    let manifest = args.json_stream_to.as_ref().map(|log_path| {
+        let params = serde_json::json!({
+            "path": args.path,
+            "min_score": args.min_score,
+            "depth": args.depth,
+            "git_root": args.git_root,
+            "dirty": args.dirty,
+            "staged": args.staged,
+            "unstaged": args.unstaged,
+            "since": args.since,
+            "format": args.format,
+            "find": args.find,
+            "group_tests": args.group_tests,
+        });
+        let files = scored
+            .iter()
+            .map(|f| ManifestFile {
+                path: f.path.clone(),
+                score: f.score,
+                tokens: None,
+                lines: None,
+                match_score: match_scores.get(&f.path).copied(),
+                group: group_of(&f.path),
+            })
+            .collect();
+        let manifest = RunManifest::new(
+            "tree",
+            output::project_name(&root, args.project_name.as_deref()),
+            params,
+            files,
+        );
+        (log_path, manifest)
+    });
+    let append_manifest = || match manifest {
+        Some((log_path, ref manifest)) => output::json::append_manifest(log_path, manifest),
+        None => Ok(()),
+    };
+
+    if args.histogram {
+        let histogram = score_histogram(&scored);
+        match format {
+            OutputFormat::Text => print_histogram(&histogram, args.no_color),
+            OutputFormat::Json => output::json::output_histogram(&histogram)?,
+            OutputFormat::Xml => output::xml::output_histogram(&histogram)?,
+            OutputFormat::JsonlPaths => {
+                return Err("--histogram does not support jsonl-paths format".into())
+            }
+        }
+        return append_manifest();
+    }
+
     match format {
         OutputFormat::Text => {
-            if args.flat {
-                print_flat(&scored, args.no_color, args.tokens, &root);
+            let print = |files: &[ScoredFile]| {
+                if args.find.is_some() {
+                    print_matches(files, &match_scores, args.no_color, args.tokens, &root);
+                } else if let Some(n) = args.flat_depth {
+                    print_grouped(files, n, args.no_color, args.tokens, &root);
+                } else if args.flat {
+                    print_flat(
+                        files,
+                        args.no_color,
+                        args.tokens,
+                        args.max_path_depth_display,
+                        &root,
+                    );
+                } else {
+                    print_tree(files, args.no_color, args.tokens, dir_scores, &root);
+                }
+            };
+
+            if args.group_tests {
+                let split = scored
+                    .iter()
+                    .position(|f| scorer.is_test(&f.path))
+                    .unwrap_or(scored.len());
+                let (source, tests) = scored.split_at(split);
+                print(source);
+                if !tests.is_empty() {
+                    println!("\n# Tests");
+                    print(tests);
+                }
             } else {
-                print_tree(&scored, args.no_color, args.tokens, &root);
+                print(&scored);
             }
         }
         OutputFormat::Json => {
-            let project_name = root
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("project")
-                .to_string();
+            let project_name = output::project_name(&root, args.project_name.as_deref());
 
             let files: Vec<FileOutput> = if args.tokens {
-                scored
-                    .par_iter()
-                    .map(|f| {
-                        let full_path = root.join(&f.path);
-                        let tokens = std::fs::read_to_string(&full_path).ok().map(|c| {
-                            crate::tokens::count_tokens(&c, "cl100k_base").unwrap_or(c.len() / 4)
-                        });
-                        FileOutput {
-                            path: f.path.clone(),
-                            score: f.score,
-                            tokens,
-                            lines: 0,
-                            content: None,
-                        }
-                    })
-                    .collect()
+                pool.install(|| {
+                    scored
+                        .par_iter()
+                        .map(|f| {
+                            let full_path = root.join(&f.path);
+                            let tokens = std::fs::read_to_string(&full_path).ok().map(|c| {
+                                crate::tokens::count_tokens(&c, "cl100k_base")
+                                    .unwrap_or(c.len() / 4)
+                            });
+                            FileOutput {
+                                path: f.path.clone(),
+                                score: f.score,
+                                tokens,
+                                lines: 0,
+                                content: None,
+                                match_score: match_scores.get(&f.path).copied(),
+                                group: group_of(&f.path),
+                            }
+                        })
+                        .collect()
+                })
             } else {
                 scored
                     .iter()
@@ -135,6 +263,8 @@ pub fn run(args: TreeArgs) -> Result<(), Box<dyn std::error::Error>> {
                         tokens: None,
                         lines: 0,
                         content: None,
+                        match_score: match_scores.get(&f.path).copied(),
+                        group: group_of(&f.path),
                     })
                     .collect()
             };
@@ -147,29 +277,30 @@ pub fn run(args: TreeArgs) -> Result<(), Box<dyn std::error::Error>> {
             output::json::output_tree(&output)?;
         }
         OutputFormat::Xml => {
-            let project_name = root
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("project")
-                .to_string();
+            let project_name = output::project_name(&root, args.project_name.as_deref());
 
             let files: Vec<FileOutput> = if args.tokens {
-                scored
-                    .par_iter()
-                    .map(|f| {
-                        let full_path = root.join(&f.path);
-                        let tokens = std::fs::read_to_string(&full_path).ok().map(|c| {
-                            crate::tokens::count_tokens(&c, "cl100k_base").unwrap_or(c.len() / 4)
-                        });
-                        FileOutput {
-                            path: f.path.clone(),
-                            score: f.score,
-                            tokens,
-                            lines: 0,
-                            content: None,
-                        }
-                    })
-                    .collect()
+                pool.install(|| {
+                    scored
+                        .par_iter()
+                        .map(|f| {
+                            let full_path = root.join(&f.path);
+                            let tokens = std::fs::read_to_string(&full_path).ok().map(|c| {
+                                crate::tokens::count_tokens(&c, "cl100k_base")
+                                    .unwrap_or(c.len() / 4)
+                            });
+                            FileOutput {
+                                path: f.path.clone(),
+                                score: f.score,
+                                tokens,
+                                lines: 0,
+                                content: None,
+                                match_score: match_scores.get(&f.path).copied(),
+                                group: group_of(&f.path),
+                            }
+                        })
+                        .collect()
+                })
             } else {
                 scored
                     .iter()
@@ -179,6 +310,8 @@ pub fn run(args: TreeArgs) -> Result<(), Box<dyn std::error::Error>> {
                         tokens: None,
                         lines: 0,
                         content: None,
+                        match_score: match_scores.get(&f.path).copied(),
+                        group: group_of(&f.path),
                     })
                     .collect()
             };
@@ -190,44 +323,246 @@ pub fn run(args: TreeArgs) -> Result<(), Box<dyn std::error::Error>> {
 
             output::xml::output_tree(&output)?;
         }
+        OutputFormat::JsonlPaths => {
+            let mut sorted: Vec<&ScoredFile> = scored.iter().collect();
+            if args.find.is_none() && !args.group_tests {
+                sorted.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.cmp(&b.path)));
+            }
+            output::json::output_paths(sorted.iter().map(|f| f.path.as_str()))?;
+        }
     }
 
-    Ok(())
+    append_manifest()
 }
 
-fn print_flat(files: &[ScoredFile], no_color: bool, show_tokens: bool, root: &Path) {
+fn print_flat(
+    files: &[ScoredFile],
+    no_color: bool,
+    show_tokens: bool,
+    max_depth_display: Option<usize>,
+    root: &Path,
+) {
     let mut sorted = files.to_vec();
     sorted.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.cmp(&b.path)));
 
     for file in sorted {
-        let score_str = format!("{:2}", file.score);
-        let colored_score = if no_color {
-            score_str
+        let display = match max_depth_display {
+            Some(max) => elide_path(&file.path, max),
+            None => file.path.clone(),
+        };
+        let mut line = format!("{} {}", format_score(file.score, no_color), display);
+
+        if show_tokens {
+            push_tokens(&mut line, root, &file.path);
+        }
+
+        println!("{}", line);
+    }
+}
+
+/// Fuzzy-match `query` against each file's basename, keyed by path.
+/// Files that don't match are absent.
+fn find_matches(files: &[ScoredFile], query: &str) -> HashMap<String, i64> {
+    let matcher = SkimMatcherV2::default();
+    files
+        .iter()
+        .filter_map(|f| {
+            let name = f.path.rsplit('/').next().unwrap_or(&f.path);
+            matcher
+                .fuzzy_match(name, query)
+                .map(|score| (f.path.clone(), score))
+        })
+        .collect()
+}
+
+/// Print `--find` results in their ranked order with match quality.
+fn print_matches(
+    files: &[ScoredFile],
+    match_scores: &HashMap<String, i64>,
+    no_color: bool,
+    show_tokens: bool,
+    root: &Path,
+) {
+    for file in files {
+        let mut line = format!("{} {}", format_score(file.score, no_color), file.path);
+
+        if show_tokens {
+            push_tokens(&mut line, root, &file.path);
+        }
+
+        let annotation = format!(" (match {})", match_scores[&file.path]);
+        if no_color {
+            line.push_str(&annotation);
+        } else {
+            line.push_str(&annotation.dimmed().to_string());
+        }
+
+        println!("{}", line);
+    }
+}
+
+/// Shorten a path with more than `max` components to its first component,
+/// `...`, and as many trailing components as fit, e.g.
+/// `src/.../handlers/user.rs` for `max = 3`.
+fn elide_path(path: &str, max: usize) -> String {
+    let components: Vec<&str> = path.split('/').collect();
+    let max = max.max(1);
+    if components.len() <= max {
+        return path.to_string();
+    }
+
+    let head = if max >= 2 { 1 } else { 0 };
+    let tail = &components[components.len() - (max - head)..];
+
+    let mut parts = components[..head].to_vec();
+    parts.push("...");
+    parts.extend_from_slice(tail);
+    parts.join("/")
+}
+
+const HISTOGRAM_WIDTH: usize = 40;
+
+fn score_histogram(files: &[ScoredFile]) -> ScoreHistogram {
+    let mut histogram: ScoreHistogram = (1..=10).map(|score| (score, 0)).collect();
+    for file in files {
+        *histogram.entry(file.score).or_insert(0) += 1;
+    }
+    histogram
+}
+
+fn print_histogram(histogram: &ScoreHistogram, no_color: bool) {
+    let max = histogram.values().copied().max().unwrap_or(0);
+
+    for (&score, &count) in histogram.iter().rev() {
+        let width = if max == 0 {
+            0
         } else {
-            match file.score {
-                8..=10 => score_str.green().bold().to_string(),
-                5..=7 => score_str.yellow().to_string(),
-                _ => score_str.dimmed().to_string(),
+            (count * HISTOGRAM_WIDTH).div_ceil(max)
+        };
+        let bar = "█".repeat(width);
+        let bar = if no_color {
+            bar
+        } else {
+            match score {
+                8..=10 => bar.green().to_string(),
+                5..=7 => bar.yellow().to_string(),
+                _ => bar.dimmed().to_string(),
             }
         };
+        println!("{:2} |{} {}", score, bar, count);
+    }
+}
 
-        let mut line = format!("{} {}", colored_score, file.path);
+fn print_grouped(
+    files: &[ScoredFile],
+    depth: usize,
+    no_color: bool,
+    show_tokens: bool,
+    root: &Path,
+) {
+    for (group, members) in group_by_depth(files, depth) {
+        let header = format!("{}/", group);
+        if no_color {
+            println!("{}", header);
+        } else {
+            println!("{}", header.blue().bold());
+        }
 
-        if show_tokens {
-            let full_path = root.join(&file.path);
-            if let Ok(content) = std::fs::read_to_string(full_path) {
-                let tokens = crate::count_tokens(&content);
-                line.push_str(&format!(" ({} tokens)", tokens));
+        for file in members {
+            let rel = if group == "." {
+                file.path.as_str()
+            } else {
+                &file.path[group.len() + 1..]
+            };
+            let mut line = format!("  {} {}", format_score(file.score, no_color), rel);
+
+            if show_tokens {
+                push_tokens(&mut line, root, &file.path);
             }
+
+            println!("{}", line);
         }
+    }
+}
 
-        println!("{}", line);
+/// Group files by their ancestor directory truncated to `depth` components.
+/// Files shallower than `depth` group under their own parent (`.` for root).
+/// Groups are ordered by highest member score, members by score then path.
+fn group_by_depth(files: &[ScoredFile], depth: usize) -> Vec<(String, Vec<&ScoredFile>)> {
+    let mut groups: HashMap<String, Vec<&ScoredFile>> = HashMap::new();
+
+    for file in files {
+        let dirs: Vec<&str> = file.path.split('/').collect();
+        let dirs = &dirs[..dirs.len() - 1];
+        let key = if dirs.is_empty() || depth == 0 {
+            ".".to_string()
+        } else {
+            dirs[..depth.min(dirs.len())].join("/")
+        };
+        groups.entry(key).or_default().push(file);
+    }
+
+    let mut grouped: Vec<(String, Vec<&ScoredFile>)> = groups.into_iter().collect();
+    for (_, members) in &mut grouped {
+        members.sort_by(|a, b| b.score.cmp(&a.score).then(a.path.cmp(&b.path)));
+    }
+    grouped.sort_by(|a, b| {
+        let max_a = a.1.first().map(|f| f.score).unwrap_or(0);
+        let max_b = b.1.first().map(|f| f.score).unwrap_or(0);
+        max_b.cmp(&max_a).then(a.0.cmp(&b.0))
+    });
+
+    grouped
+}
+
+fn format_score(score: i32, no_color: bool) -> String {
+    let score_str = format!("{:2}", score);
+    if no_color {
+        score_str
+    } else {
+        match score {
+            8..=10 => score_str.green().bold().to_string(),
+            5..=7 => score_str.yellow().to_string(),
+            _ => score_str.dimmed().to_string(),
+        }
+    }
+}
+
+fn push_tokens(line: &mut String, root: &Path, path: &str) {
+    if let Ok(content) = std::fs::read_to_string(root.join(path)) {
+        let tokens = crate::count_tokens(&content);
+        line.push_str(&format!(" ({} tokens)", tokens));
     }
 }
 
-fn print_tree(files: &[ScoredFile], no_color: bool, show_tokens: bool, root: &Path) {
+fn print_tree(
+    files: &[ScoredFile],
+    no_color: bool,
+    show_tokens: bool,
+    dir_scores: Option<DirScore>,
+    root: &Path,
+) {
     let tree = build_tree(files);
-    print_node(&tree, "", true, no_color, show_tokens, root);
+    print_node(&tree, "", true, no_color, show_tokens, dir_scores, root);
+}
+
+/// Aggregate shown next to directories with `--dir-scores`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirScore {
+    Max,
+    Avg,
+}
+
+impl std::str::FromStr for DirScore {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "max" => Ok(DirScore::Max),
+            "avg" => Ok(DirScore::Avg),
+            _ => Err(format!("Invalid dir score: {}. Use max or avg", s)),
+        }
+    }
 }
 
 struct TreeNode {
@@ -288,42 +623,74 @@ fn get_max_score(node: &TreeNode) -> i32 {
     max
 }
 
+/// Sum and count of file scores under `node`, for averaging.
+fn score_totals(node: &TreeNode) -> (i32, usize) {
+    let mut totals = match node.score {
+        Some(score) if node.children.is_empty() => (score, 1),
+        _ => (0, 0),
+    };
+
+    for child in node.children.values() {
+        let (sum, count) = score_totals(child);
+        totals.0 += sum;
+        totals.1 += count;
+    }
+
+    totals
+}
+
+fn dir_score_label(node: &TreeNode, mode: DirScore) -> String {
+    match mode {
+        DirScore::Max => format!("[max {}]", get_max_score(node)),
+        DirScore::Avg => {
+            let (sum, count) = score_totals(node);
+            let avg = if count == 0 {
+                0.0
+            } else {
+                sum as f64 / count as f64
+            };
+            format!("[avg {:.1}]", avg)
+        }
+    }
+}
+
 fn print_node(
     node: &TreeNode,
     prefix: &str,
     is_last: bool,
     no_color: bool,
     show_tokens: bool,
+    dir_scores: Option<DirScore>,
     root: &Path,
 ) {
     if node.name != "." {
         let connector = if is_last { "└── " } else { "├── " };
 
         let display_name = if node.children.is_empty() {
-            let score_str = format!("{:2}", node.score.unwrap_or(0));
-            let colored_score = if no_color {
-                score_str
+            let mut name = format!(
+                "{} {}",
+                format_score(node.score.unwrap_or(0), no_color),
+                node.name
+            );
+            if show_tokens {
+                push_tokens(&mut name, root, &node.path);
+            }
+            name
+        } else {
+            let mut name = if no_color {
+                format!("{}/", node.name)
             } else {
-                match node.score.unwrap_or(0) {
-                    8..=10 => score_str.green().bold().to_string(),
-                    5..=7 => score_str.yellow().to_string(),
-                    _ => score_str.dimmed().to_string(),
-                }
+                format!("{}", node.name.blue().bold())
             };
-
-            let mut name = format!("{} {}", colored_score, node.name);
-            if show_tokens {
-                let full_path = root.join(&node.path);
-                if let Ok(content) = std::fs::read_to_string(full_path) {
-                    let tokens = crate::count_tokens(&content);
-                    name.push_str(&format!(" ({} tokens)", tokens));
+            if let Some(mode) = dir_scores {
+                let label = dir_score_label(node, mode);
+                if no_color {
+                    name.push_str(&format!(" {}", label));
+                } else {
+                    name.push_str(&format!(" {}", label.dimmed()));
                 }
             }
             name
-        } else if no_color {
-            format!("{}/", node.name)
-        } else {
-            format!("{}", node.name.blue().bold())
         };
 
         println!("{}{}{}", prefix, connector, display_name);
@@ -349,6 +716,7 @@ fn print_node(
             is_last_child,
             no_color,
             show_tokens,
+            dir_scores,
             root,
         );
     }
@@ -432,4 +800,108 @@ mod tests {
         assert!(scored.iter().any(|f| f.path == "dir/b.rs"));
         assert!(!scored.iter().any(|f| f.path == "dir/subdir/c.rs"));
     }
+
+    fn scored(path: &str, score: i32) -> ScoredFile {
+        ScoredFile {
+            path: path.to_string(),
+            score,
+            reason: "".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_matches() {
+        let files = vec![
+            scored("src/handler.rs", 9),
+            scored("src/handlers/user.rs", 8),
+            scored("README.md", 10),
+        ];
+        let matches = find_matches(&files, "hndlr");
+        assert!(matches.contains_key("src/handler.rs"));
+        // Only the basename is matched, not directories.
+        assert!(!matches.contains_key("src/handlers/user.rs"));
+        assert!(!matches.contains_key("README.md"));
+    }
+
+    #[test]
+    fn test_elide_path() {
+        assert_eq!(
+            elide_path("src/a/b/handlers/user.rs", 3),
+            "src/.../handlers/user.rs"
+        );
+        assert_eq!(elide_path("src/a/b/handlers/user.rs", 1), ".../user.rs");
+        assert_eq!(
+            elide_path("src/handlers/user.rs", 3),
+            "src/handlers/user.rs"
+        );
+        assert_eq!(elide_path("user.rs", 1), "user.rs");
+    }
+
+    #[test]
+    fn test_group_by_depth() {
+        let files = vec![
+            scored("README.md", 10),
+            scored("src/main.rs", 10),
+            scored("src/handlers/user.rs", 8),
+            scored("src/handlers/admin/panel.rs", 7),
+            scored("tests/cli.rs", 5),
+        ];
+
+        let groups = group_by_depth(&files, 1);
+        let names: Vec<&str> = groups.iter().map(|(g, _)| g.as_str()).collect();
+        assert_eq!(names, vec![".", "src", "tests"]);
+        let src: Vec<&str> = groups[1].1.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            src,
+            vec![
+                "src/main.rs",
+                "src/handlers/user.rs",
+                "src/handlers/admin/panel.rs"
+            ]
+        );
+
+        let groups = group_by_depth(&files, 2);
+        let names: Vec<&str> = groups.iter().map(|(g, _)| g.as_str()).collect();
+        assert_eq!(names, vec![".", "src", "src/handlers", "tests"]);
+        let handlers: Vec<&str> = groups[2].1.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            handlers,
+            vec!["src/handlers/user.rs", "src/handlers/admin/panel.rs"]
+        );
+    }
+
+    #[test]
+    fn test_score_histogram() {
+        let files = vec![
+            scored("README.md", 10),
+            scored("src/main.rs", 10),
+            scored("tests/cli.rs", 5),
+        ];
+
+        let histogram = score_histogram(&files);
+        assert_eq!(histogram.len(), 10);
+        assert_eq!(histogram[&10], 2);
+        assert_eq!(histogram[&5], 1);
+        assert_eq!(histogram[&1], 0);
+    }
+
+    #[test]
+    fn test_dir_score_aggregates() {
+        let files = vec![
+            scored("src/main.rs", 10),
+            scored("src/util.rs", 7),
+            scored("src/handlers/user.rs", 8),
+            scored("README.md", 10),
+        ];
+        let root = build_tree(&files);
+        let src = &root.children["src"];
+
+        assert_eq!(dir_score_label(src, DirScore::Max), "[max 10]");
+        assert_eq!(score_totals(src), (25, 3));
+        assert_eq!(dir_score_label(src, DirScore::Avg), "[avg 8.3]");
+
+        let handlers = &src.children["handlers"];
+        assert_eq!(dir_score_label(handlers, DirScore::Avg), "[avg 8.0]");
+        assert_eq!(score_totals(&root), (35, 4));
+    }
 }
@@ -198,3 +198,132 @@ fn test_default_text_format_unchanged() {
         .stdout(predicate::str::contains("<?xml").not())
         .stdout(predicate::str::contains(r#""project""#).not());
 }
+
+#[test]
+fn test_tree_jsonl_paths_output() {
+    let dir = tempdir().unwrap();
+    let repo_path = dir.path();
+
+    StdCommand::new("git")
+        .args(["init"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    fs::write(repo_path.join("README.md"), "# Test").unwrap();
+    fs::create_dir(repo_path.join("src")).unwrap();
+    fs::write(repo_path.join("src/main.rs"), "fn main() {}").unwrap();
+    fs::write(repo_path.join("src/my file.rs"), "fn spaced() {}").unwrap();
+
+    StdCommand::new("git")
+        .args(["add", "."])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("om").unwrap();
+    cmd.current_dir(repo_path)
+        .args(["tree", "--format", "jsonl-paths"]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let paths: Vec<String> = stdout
+        .lines()
+        .map(|line| serde_json::from_str::<String>(line).unwrap())
+        .collect();
+
+    assert_eq!(paths.len(), 3);
+    assert!(paths.contains(&"README.md".to_string()));
+    assert!(paths.contains(&"src/main.rs".to_string()));
+    assert!(paths.contains(&"src/my file.rs".to_string()));
+}
+
+#[test]
+fn test_cat_rejects_jsonl_paths() {
+    let dir = tempdir().unwrap();
+
+    StdCommand::new("git")
+        .args(["init"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    let mut cmd = Command::cargo_bin("om").unwrap();
+    let output = cmd
+        .current_dir(dir.path())
+        .args(["cat", "--format", "jsonl-paths"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("cat supports text, json, xml"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_cat_template_output() {
+    let dir = tempdir().unwrap();
+    let repo_path = dir.path();
+
+    StdCommand::new("git")
+        .args(["init"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    fs::write(repo_path.join("a.txt"), "alpha").unwrap();
+    fs::write(repo_path.join("b.txt"), "beta").unwrap();
+    let template = dir.path().join("prompt.tpl");
+    fs::write(
+        &template,
+        "Files:{{ for file in files }} {file.path}{{ endfor }}\n\
+         {{ for file in files }}[{file.path}]\n{file.content}\n{{ endfor }}",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("om").unwrap();
+    cmd.current_dir(repo_path)
+        .args(["cat", "a.txt", "b.txt", "--template"])
+        .arg(&template)
+        .assert()
+        .success()
+        .stdout("Files: a.txt b.txt\n[a.txt]\nalpha\n[b.txt]\nbeta\n");
+}
+
+#[test]
+fn test_project_name_override() {
+    let dir = tempdir().unwrap();
+    let repo_path = dir.path();
+
+    StdCommand::new("git")
+        .args(["init"])
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+
+    fs::write(repo_path.join("main.rs"), "fn main() {}\n").unwrap();
+
+    for command in ["tree", "cat"] {
+        let mut cmd = Command::cargo_bin("om").unwrap();
+        let output = cmd
+            .current_dir(repo_path)
+            .args([command, "--format", "json", "--project-name", "billing-api"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let v: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(v["project"], "billing-api", "{}", command);
+    }
+
+    let mut cmd = Command::cargo_bin("om").unwrap();
+    cmd.current_dir(repo_path)
+        .args(["cat", "main.rs", "--project-name", "billing-api"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("# Project: billing-api\n"));
+}